@@ -0,0 +1,46 @@
+use std::fmt;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use crate::Value;
+
+// Exception tags. Every tag, whether defined by the host or by a module, gets
+// its own identity, which is what a `catch` clause matches on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Tag(u32);
+
+impl Tag {
+    pub(crate) fn new() -> Self {
+        static NEXT_TAG: AtomicU32 = AtomicU32::new(0);
+        Self(NEXT_TAG.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+// A wasm exception. Uncaught exceptions are returned as errors from the `Vm`,
+// and imports throw one by returning it as their error.
+#[derive(Debug, Clone)]
+pub struct Exception {
+    tag: Tag,
+    values: Vec<Value>,
+}
+
+impl Exception {
+    pub fn new(tag: Tag, values: Vec<Value>) -> Self {
+        Self { tag, values }
+    }
+
+    pub fn tag(&self) -> Tag {
+        self.tag
+    }
+
+    pub fn values(&self) -> &[Value] {
+        &self.values
+    }
+}
+
+impl fmt::Display for Exception {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "uncaught wasm exception")
+    }
+}
+
+impl std::error::Error for Exception {}
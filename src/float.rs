@@ -0,0 +1,74 @@
+use anyhow::Result;
+
+use crate::trap::{Trap, TrapKind};
+
+// Float operations whose WebAssembly semantics differ from Rust's std ones.
+// `f32::min`/`f32::max` ignore NaN operands and don't order -0 below +0, but
+// wasm propagates NaN and treats -0 as the smaller zero.
+
+pub(crate) fn f32_min(a: f32, b: f32) -> f32 {
+    if a.is_nan() || b.is_nan() {
+        a + b
+    } else if a == b {
+        f32::from_bits(a.to_bits() | b.to_bits())
+    } else {
+        a.min(b)
+    }
+}
+
+pub(crate) fn f32_max(a: f32, b: f32) -> f32 {
+    if a.is_nan() || b.is_nan() {
+        a + b
+    } else if a == b {
+        f32::from_bits(a.to_bits() & b.to_bits())
+    } else {
+        a.max(b)
+    }
+}
+
+pub(crate) fn f64_min(a: f64, b: f64) -> f64 {
+    if a.is_nan() || b.is_nan() {
+        a + b
+    } else if a == b {
+        f64::from_bits(a.to_bits() | b.to_bits())
+    } else {
+        a.min(b)
+    }
+}
+
+pub(crate) fn f64_max(a: f64, b: f64) -> f64 {
+    if a.is_nan() || b.is_nan() {
+        a + b
+    } else if a == b {
+        f64::from_bits(a.to_bits() & b.to_bits())
+    } else {
+        a.max(b)
+    }
+}
+
+// Floats that come out of arithmetic as NaN, whether scalars or vector lanes,
+// are set to the positive canonical NaN. Hosts disagree on the sign and
+// payload of the NaNs their hardware produces, and wasm allows either, so this
+// keeps results bit-exact across hosts.
+pub(crate) fn canonicalize_f32(value: f32) -> f32 {
+    if value.to_bits() & 0x7fff_ffff > 0x7f80_0000 { f32::from_bits(0x7fc0_0000) } else { value }
+}
+
+pub(crate) fn canonicalize_f64(value: f64) -> f64 {
+    if value.to_bits() & 0x7fff_ffff_ffff_ffff > 0x7ff0_0000_0000_0000 { f64::from_bits(0x7ff8_0000_0000_0000) } else { value }
+}
+
+// Truncates `value` towards zero for a trapping float-to-int conversion. The
+// result must lie in `min..max`; both bounds are exactly representable, and
+// f32 operands are widened to f64 losslessly. The saturating conversions need
+// no help, as Rust's `as` casts saturate and map NaN to zero already.
+pub(crate) fn checked_trunc(value: f64, min: f64, max: f64) -> Result<f64> {
+    if value.is_nan() {
+        return Err(Trap::new(TrapKind::InvalidConversionToInteger).into());
+    }
+    let truncated = value.trunc();
+    if truncated < min || truncated >= max {
+        return Err(Trap::new(TrapKind::IntegerOverflow).into());
+    }
+    Ok(truncated)
+}
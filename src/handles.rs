@@ -0,0 +1,74 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::Result;
+
+// Objects the guest refers to by handle: host objects behind `externref`s and
+// caught exceptions behind `exnref`s. Wasm only ever sees the handle of an
+// object, and handles are never reused, so a stale one can't alias a newer
+// object.
+struct Entry<T> {
+    object: T,
+    // References held by the host, on top of those held by the guest.
+    host_refs: u32,
+}
+
+pub(crate) struct Handles<T> {
+    objects: HashMap<u32, Entry<T>>,
+    next_handle: u32,
+}
+
+impl<T> Default for Handles<T> {
+    fn default() -> Self {
+        Self { objects: HashMap::new(), next_handle: 0 }
+    }
+}
+
+impl<T> Handles<T> {
+    // Adds an object only the guest refers to so far.
+    pub(crate) fn insert(&mut self, object: T) -> Result<u32> {
+        let handle = self.next_handle;
+        self.next_handle = handle.checked_add(1).ok_or_else(|| anyhow::anyhow!("Out of reference handles"))?;
+        self.objects.insert(handle, Entry { object, host_refs: 0 });
+        Ok(handle)
+    }
+
+    pub(crate) fn get(&self, handle: u32) -> Option<&T> {
+        self.objects.get(&handle).map(|entry| &entry.object)
+    }
+
+    pub(crate) fn get_mut(&mut self, handle: u32) -> Option<&mut T> {
+        self.objects.get_mut(&handle).map(|entry| &mut entry.object)
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    pub(crate) fn objects(&self) -> impl Iterator<Item = &T> {
+        self.objects.values().map(|entry| &entry.object)
+    }
+
+    // The handles of the objects the host holds.
+    pub(crate) fn held(&self) -> impl Iterator<Item = u32> + '_ {
+        self.objects.iter().filter(|(_, entry)| entry.host_refs > 0).map(|(handle, _)| *handle)
+    }
+
+    pub(crate) fn retain(&mut self, handle: u32) {
+        if let Some(entry) = self.objects.get_mut(&handle) {
+            entry.host_refs += 1;
+        }
+    }
+
+    pub(crate) fn release(&mut self, handle: u32) {
+        if let Some(entry) = self.objects.get_mut(&handle) {
+            entry.host_refs = entry.host_refs.saturating_sub(1);
+        }
+    }
+
+    // Drops every object the host no longer holds and that isn't among the
+    // handles still reachable from the guest.
+    pub(crate) fn collect(&mut self, reachable: impl IntoIterator<Item = u32>) {
+        let reachable: HashSet<u32> = reachable.into_iter().collect();
+        self.objects.retain(|handle, entry| entry.host_refs > 0 || reachable.contains(handle));
+    }
+}
@@ -1,10 +1,20 @@
 use std::collections::HashMap;
-use wasmparser::Parser;
+use std::sync::atomic::{AtomicU32, Ordering};
+use wasmparser::{Parser, WasmFeatures};
 use anyhow::Result;
 
+mod exception;
+mod float;
+mod handles;
+mod memory;
+mod simd;
+mod table;
+mod trap;
 mod vm;
 
-pub use vm::Vm;
+pub use exception::{Exception, Tag};
+pub use trap::{Trap, TrapKind};
+pub use vm::{Config, Vm};
 
 // Value Types
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -13,56 +23,978 @@ pub enum ValType {
     I64,
     F32,
     F64,
+    V128,
+    FuncRef,
+    ExternRef,
+    ExnRef,
 }
 
-impl From<wasmparser::ValType> for ValType {
-    fn from(val_type: wasmparser::ValType) -> Self {
+// Only the nullable `funcref`, `externref` and `exnref` references are
+// supported; typed references are rejected rather than mapped loosely.
+impl TryFrom<wasmparser::ValType> for ValType {
+    type Error = anyhow::Error;
+
+    fn try_from(val_type: wasmparser::ValType) -> Result<Self> {
         match val_type {
-            wasmparser::ValType::I32 => ValType::I32,
-            wasmparser::ValType::I64 => ValType::I64,
-            wasmparser::ValType::F32 => ValType::F32,
-            wasmparser::ValType::F64 => ValType::F64,
-            _ => unimplemented!("Unsupported value type"),
+            wasmparser::ValType::I32 => Ok(ValType::I32),
+            wasmparser::ValType::I64 => Ok(ValType::I64),
+            wasmparser::ValType::F32 => Ok(ValType::F32),
+            wasmparser::ValType::F64 => Ok(ValType::F64),
+            wasmparser::ValType::V128 => Ok(ValType::V128),
+            wasmparser::ValType::Ref(wasmparser::RefType::FUNCREF) => Ok(ValType::FuncRef),
+            wasmparser::ValType::Ref(wasmparser::RefType::EXTERNREF) => Ok(ValType::ExternRef),
+            wasmparser::ValType::Ref(wasmparser::RefType::EXNREF) => Ok(ValType::ExnRef),
+            _ => Err(anyhow::anyhow!("Unsupported value type {}", val_type)),
         }
     }
 }
 
+// Memory immediates. The alignment is only a hint, so it is not kept around.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct MemArg {
+    offset: u64,
+    memory: u32,
+}
+
+impl From<wasmparser::MemArg> for MemArg {
+    fn from(memarg: wasmparser::MemArg) -> Self {
+        Self { offset: memarg.offset, memory: memarg.memory }
+    }
+}
+
 // Instructions
 #[derive(Debug)]
 pub(crate) enum Instruction {
     I32Add,
     I32Sub,
     I32Mul,
-    I32Div,
-    I32Rem,
+    I32DivS,
+    I32RemS,
     I32And,
     I32Or,
     I32Xor,
     I32Shl,
+    I32DivU,
+    I32RemU,
+    I32ShrS,
+    I32ShrU,
+    I32Rotl,
+    I32Rotr,
+    I32Clz,
+    I32Ctz,
+    I32Popcnt,
+    I32Eqz,
+    I32Eq,
+    I32Ne,
+    I32LtS,
+    I32LtU,
+    I32GtS,
+    I32GtU,
+    I32LeS,
+    I32LeU,
+    I32GeS,
+    I32GeU,
+    I32Extend8S,
+    I32Extend16S,
     I32Const(i32),
+    I64Add,
+    I64Sub,
+    I64Mul,
+    I64DivS,
+    I64DivU,
+    I64RemS,
+    I64RemU,
+    I64And,
+    I64Or,
+    I64Xor,
+    I64Shl,
+    I64ShrS,
+    I64ShrU,
+    I64Rotl,
+    I64Rotr,
+    I64Clz,
+    I64Ctz,
+    I64Popcnt,
+    I64Eqz,
+    I64Eq,
+    I64Ne,
+    I64LtS,
+    I64LtU,
+    I64GtS,
+    I64GtU,
+    I64LeS,
+    I64LeU,
+    I64GeS,
+    I64GeU,
+    I64Extend8S,
+    I64Extend16S,
+    I64Extend32S,
+    I32WrapI64,
+    I64ExtendI32S,
+    I64ExtendI32U,
+    I64Const(i64),
+    F32Abs,
+    F32Neg,
+    F32Ceil,
+    F32Floor,
+    F32Trunc,
+    F32Nearest,
+    F32Sqrt,
+    F32Add,
+    F32Sub,
+    F32Mul,
+    F32Div,
+    F32Min,
+    F32Max,
+    F32Copysign,
+    F32Eq,
+    F32Ne,
+    F32Lt,
+    F32Gt,
+    F32Le,
+    F32Ge,
+    F64Abs,
+    F64Neg,
+    F64Ceil,
+    F64Floor,
+    F64Trunc,
+    F64Nearest,
+    F64Sqrt,
+    F64Add,
+    F64Sub,
+    F64Mul,
+    F64Div,
+    F64Min,
+    F64Max,
+    F64Copysign,
+    F64Eq,
+    F64Ne,
+    F64Lt,
+    F64Gt,
+    F64Le,
+    F64Ge,
+    F32Const(u32),
+    F64Const(u64),
+    I32TruncF32S,
+    I32TruncF32U,
+    I32TruncF64S,
+    I32TruncF64U,
+    I64TruncF32S,
+    I64TruncF32U,
+    I64TruncF64S,
+    I64TruncF64U,
+    I32TruncSatF32S,
+    I32TruncSatF32U,
+    I32TruncSatF64S,
+    I32TruncSatF64U,
+    I64TruncSatF32S,
+    I64TruncSatF32U,
+    I64TruncSatF64S,
+    I64TruncSatF64U,
+    F32ConvertI32S,
+    F32ConvertI32U,
+    F32ConvertI64S,
+    F32ConvertI64U,
+    F32DemoteF64,
+    F64ConvertI32S,
+    F64ConvertI32U,
+    F64ConvertI64S,
+    F64ConvertI64U,
+    F64PromoteF32,
+    I32ReinterpretF32,
+    I64ReinterpretF64,
+    F32ReinterpretI32,
+    F64ReinterpretI64,
+    V128Load(MemArg),
+    V128Load8x8S(MemArg),
+    V128Load8x8U(MemArg),
+    V128Load16x4S(MemArg),
+    V128Load16x4U(MemArg),
+    V128Load32x2S(MemArg),
+    V128Load32x2U(MemArg),
+    V128Load8Splat(MemArg),
+    V128Load16Splat(MemArg),
+    V128Load32Splat(MemArg),
+    V128Load64Splat(MemArg),
+    V128Load32Zero(MemArg),
+    V128Load64Zero(MemArg),
+    V128Store(MemArg),
+    V128Load8Lane { memarg: MemArg, lane: u8 },
+    V128Load16Lane { memarg: MemArg, lane: u8 },
+    V128Load32Lane { memarg: MemArg, lane: u8 },
+    V128Load64Lane { memarg: MemArg, lane: u8 },
+    V128Store8Lane { memarg: MemArg, lane: u8 },
+    V128Store16Lane { memarg: MemArg, lane: u8 },
+    V128Store32Lane { memarg: MemArg, lane: u8 },
+    V128Store64Lane { memarg: MemArg, lane: u8 },
+    V128Const(u128),
+    I8x16Shuffle([u8; 16]),
+    I8x16ExtractLaneS(u8),
+    I8x16ExtractLaneU(u8),
+    I8x16ReplaceLane(u8),
+    I16x8ExtractLaneS(u8),
+    I16x8ExtractLaneU(u8),
+    I16x8ReplaceLane(u8),
+    I32x4ExtractLane(u8),
+    I32x4ReplaceLane(u8),
+    I64x2ExtractLane(u8),
+    I64x2ReplaceLane(u8),
+    F32x4ExtractLane(u8),
+    F32x4ReplaceLane(u8),
+    F64x2ExtractLane(u8),
+    F64x2ReplaceLane(u8),
+    I8x16Swizzle,
+    I8x16Splat,
+    I16x8Splat,
+    I32x4Splat,
+    I64x2Splat,
+    F32x4Splat,
+    F64x2Splat,
+    I8x16Eq,
+    I8x16Ne,
+    I8x16LtS,
+    I8x16LtU,
+    I8x16GtS,
+    I8x16GtU,
+    I8x16LeS,
+    I8x16LeU,
+    I8x16GeS,
+    I8x16GeU,
+    I16x8Eq,
+    I16x8Ne,
+    I16x8LtS,
+    I16x8LtU,
+    I16x8GtS,
+    I16x8GtU,
+    I16x8LeS,
+    I16x8LeU,
+    I16x8GeS,
+    I16x8GeU,
+    I32x4Eq,
+    I32x4Ne,
+    I32x4LtS,
+    I32x4LtU,
+    I32x4GtS,
+    I32x4GtU,
+    I32x4LeS,
+    I32x4LeU,
+    I32x4GeS,
+    I32x4GeU,
+    I64x2Eq,
+    I64x2Ne,
+    I64x2LtS,
+    I64x2GtS,
+    I64x2LeS,
+    I64x2GeS,
+    F32x4Eq,
+    F32x4Ne,
+    F32x4Lt,
+    F32x4Gt,
+    F32x4Le,
+    F32x4Ge,
+    F64x2Eq,
+    F64x2Ne,
+    F64x2Lt,
+    F64x2Gt,
+    F64x2Le,
+    F64x2Ge,
+    V128Not,
+    V128And,
+    V128AndNot,
+    V128Or,
+    V128Xor,
+    V128Bitselect,
+    V128AnyTrue,
+    I8x16Abs,
+    I8x16Neg,
+    I8x16Popcnt,
+    I8x16AllTrue,
+    I8x16Bitmask,
+    I8x16NarrowI16x8S,
+    I8x16NarrowI16x8U,
+    I8x16Shl,
+    I8x16ShrS,
+    I8x16ShrU,
+    I8x16Add,
+    I8x16AddSatS,
+    I8x16AddSatU,
+    I8x16Sub,
+    I8x16SubSatS,
+    I8x16SubSatU,
+    I8x16MinS,
+    I8x16MinU,
+    I8x16MaxS,
+    I8x16MaxU,
+    I8x16AvgrU,
+    I16x8ExtAddPairwiseI8x16S,
+    I16x8ExtAddPairwiseI8x16U,
+    I16x8Abs,
+    I16x8Neg,
+    I16x8Q15MulrSatS,
+    I16x8AllTrue,
+    I16x8Bitmask,
+    I16x8NarrowI32x4S,
+    I16x8NarrowI32x4U,
+    I16x8ExtendLowI8x16S,
+    I16x8ExtendHighI8x16S,
+    I16x8ExtendLowI8x16U,
+    I16x8ExtendHighI8x16U,
+    I16x8Shl,
+    I16x8ShrS,
+    I16x8ShrU,
+    I16x8Add,
+    I16x8AddSatS,
+    I16x8AddSatU,
+    I16x8Sub,
+    I16x8SubSatS,
+    I16x8SubSatU,
+    I16x8Mul,
+    I16x8MinS,
+    I16x8MinU,
+    I16x8MaxS,
+    I16x8MaxU,
+    I16x8AvgrU,
+    I16x8ExtMulLowI8x16S,
+    I16x8ExtMulHighI8x16S,
+    I16x8ExtMulLowI8x16U,
+    I16x8ExtMulHighI8x16U,
+    I32x4ExtAddPairwiseI16x8S,
+    I32x4ExtAddPairwiseI16x8U,
+    I32x4Abs,
+    I32x4Neg,
+    I32x4AllTrue,
+    I32x4Bitmask,
+    I32x4ExtendLowI16x8S,
+    I32x4ExtendHighI16x8S,
+    I32x4ExtendLowI16x8U,
+    I32x4ExtendHighI16x8U,
+    I32x4Shl,
+    I32x4ShrS,
+    I32x4ShrU,
+    I32x4Add,
+    I32x4Sub,
+    I32x4Mul,
+    I32x4MinS,
+    I32x4MinU,
+    I32x4MaxS,
+    I32x4MaxU,
+    I32x4DotI16x8S,
+    I32x4ExtMulLowI16x8S,
+    I32x4ExtMulHighI16x8S,
+    I32x4ExtMulLowI16x8U,
+    I32x4ExtMulHighI16x8U,
+    I64x2Abs,
+    I64x2Neg,
+    I64x2AllTrue,
+    I64x2Bitmask,
+    I64x2ExtendLowI32x4S,
+    I64x2ExtendHighI32x4S,
+    I64x2ExtendLowI32x4U,
+    I64x2ExtendHighI32x4U,
+    I64x2Shl,
+    I64x2ShrS,
+    I64x2ShrU,
+    I64x2Add,
+    I64x2Sub,
+    I64x2Mul,
+    I64x2ExtMulLowI32x4S,
+    I64x2ExtMulHighI32x4S,
+    I64x2ExtMulLowI32x4U,
+    I64x2ExtMulHighI32x4U,
+    F32x4Ceil,
+    F32x4Floor,
+    F32x4Trunc,
+    F32x4Nearest,
+    F32x4Abs,
+    F32x4Neg,
+    F32x4Sqrt,
+    F32x4Add,
+    F32x4Sub,
+    F32x4Mul,
+    F32x4Div,
+    F32x4Min,
+    F32x4Max,
+    F32x4PMin,
+    F32x4PMax,
+    F64x2Ceil,
+    F64x2Floor,
+    F64x2Trunc,
+    F64x2Nearest,
+    F64x2Abs,
+    F64x2Neg,
+    F64x2Sqrt,
+    F64x2Add,
+    F64x2Sub,
+    F64x2Mul,
+    F64x2Div,
+    F64x2Min,
+    F64x2Max,
+    F64x2PMin,
+    F64x2PMax,
+    I32x4TruncSatF32x4S,
+    I32x4TruncSatF32x4U,
+    F32x4ConvertI32x4S,
+    F32x4ConvertI32x4U,
+    I32x4TruncSatF64x2SZero,
+    I32x4TruncSatF64x2UZero,
+    F64x2ConvertLowI32x4S,
+    F64x2ConvertLowI32x4U,
+    F32x4DemoteF64x2Zero,
+    F64x2PromoteLowF32x4,
+    I8x16RelaxedSwizzle,
+    I32x4RelaxedTruncF32x4S,
+    I32x4RelaxedTruncF32x4U,
+    I32x4RelaxedTruncF64x2SZero,
+    I32x4RelaxedTruncF64x2UZero,
+    F32x4RelaxedMadd,
+    F32x4RelaxedNmadd,
+    F64x2RelaxedMadd,
+    F64x2RelaxedNmadd,
+    I8x16RelaxedLaneselect,
+    I16x8RelaxedLaneselect,
+    I32x4RelaxedLaneselect,
+    I64x2RelaxedLaneselect,
+    F32x4RelaxedMin,
+    F32x4RelaxedMax,
+    F64x2RelaxedMin,
+    F64x2RelaxedMax,
+    I16x8RelaxedQ15mulrS,
+    I16x8RelaxedDotI8x16I7x16S,
+    I32x4RelaxedDotI8x16I7x16AddS,
     Call(u32),
+    CallIndirect { type_index: u32, table: u32 },
+    ReturnCall(u32),
+    ReturnCallIndirect { type_index: u32, table: u32 },
+    Unreachable,
+    Nop,
+    Drop,
+    Select,
     LocalGet(u32),
     LocalSet(u32),
+    LocalTee(u32),
     GlobalGet(u32),
     GlobalSet(u32),
+    I32Load(MemArg),
+    I64Load(MemArg),
+    F32Load(MemArg),
+    F64Load(MemArg),
+    I32Load8S(MemArg),
+    I32Load8U(MemArg),
+    I32Load16S(MemArg),
+    I32Load16U(MemArg),
+    I64Load8S(MemArg),
+    I64Load8U(MemArg),
+    I64Load16S(MemArg),
+    I64Load16U(MemArg),
+    I64Load32S(MemArg),
+    I64Load32U(MemArg),
+    I32Store(MemArg),
+    I64Store(MemArg),
+    F32Store(MemArg),
+    F64Store(MemArg),
+    I32Store8(MemArg),
+    I32Store16(MemArg),
+    I64Store8(MemArg),
+    I64Store16(MemArg),
+    I64Store32(MemArg),
+    MemorySize(u32),
+    MemoryGrow(u32),
+    MemoryInit { data: u32, memory: u32 },
+    DataDrop(u32),
+    MemoryCopy { destination: u32, source: u32 },
+    MemoryFill(u32),
+    TableGet(u32),
+    TableSet(u32),
+    TableSize(u32),
+    TableGrow(u32),
+    TableFill(u32),
+    TableCopy { destination: u32, source: u32 },
+    TableInit { element: u32, table: u32 },
+    ElemDrop(u32),
+    RefNull,
+    RefIsNull,
+    RefFunc(u32),
+    Block { params: u32, arity: u32, end: u32 },
+    TryTable { params: u32, arity: u32, end: u32, catches: Box<[Catch]> },
+    Throw(u32),
+    ThrowRef,
+    Loop { arity: u32 },
+    If { params: u32, arity: u32, else_: u32, end: u32 },
+    Else { end: u32 },
+    Br(u32),
+    BrIf(u32),
+    BrTable { targets: Box<[u32]>, default: u32 },
     End,
     Return,
 }
 
-impl<'a> From<wasmparser::Operator<'a>> for Instruction {
-    fn from(operator: wasmparser::Operator<'a>) -> Self {
-        match operator {
+impl<'a> TryFrom<wasmparser::Operator<'a>> for Instruction {
+    type Error = anyhow::Error;
+
+    fn try_from(operator: wasmparser::Operator<'a>) -> Result<Self> {
+        let instruction = match operator {
             wasmparser::Operator::I32Add => Instruction::I32Add,
             wasmparser::Operator::I32Sub => Instruction::I32Sub,
             wasmparser::Operator::I32Mul => Instruction::I32Mul,
+            wasmparser::Operator::I32DivS => Instruction::I32DivS,
+            wasmparser::Operator::I32RemS => Instruction::I32RemS,
+            wasmparser::Operator::I32And => Instruction::I32And,
+            wasmparser::Operator::I32Or => Instruction::I32Or,
+            wasmparser::Operator::I32Xor => Instruction::I32Xor,
+            wasmparser::Operator::I32Shl => Instruction::I32Shl,
+            wasmparser::Operator::I32DivU => Instruction::I32DivU,
+            wasmparser::Operator::I32RemU => Instruction::I32RemU,
+            wasmparser::Operator::I32ShrS => Instruction::I32ShrS,
+            wasmparser::Operator::I32ShrU => Instruction::I32ShrU,
+            wasmparser::Operator::I32Rotl => Instruction::I32Rotl,
+            wasmparser::Operator::I32Rotr => Instruction::I32Rotr,
+            wasmparser::Operator::I32Clz => Instruction::I32Clz,
+            wasmparser::Operator::I32Ctz => Instruction::I32Ctz,
+            wasmparser::Operator::I32Popcnt => Instruction::I32Popcnt,
+            wasmparser::Operator::I32Eqz => Instruction::I32Eqz,
+            wasmparser::Operator::I32Eq => Instruction::I32Eq,
+            wasmparser::Operator::I32Ne => Instruction::I32Ne,
+            wasmparser::Operator::I32LtS => Instruction::I32LtS,
+            wasmparser::Operator::I32LtU => Instruction::I32LtU,
+            wasmparser::Operator::I32GtS => Instruction::I32GtS,
+            wasmparser::Operator::I32GtU => Instruction::I32GtU,
+            wasmparser::Operator::I32LeS => Instruction::I32LeS,
+            wasmparser::Operator::I32LeU => Instruction::I32LeU,
+            wasmparser::Operator::I32GeS => Instruction::I32GeS,
+            wasmparser::Operator::I32GeU => Instruction::I32GeU,
+            wasmparser::Operator::I32Extend8S => Instruction::I32Extend8S,
+            wasmparser::Operator::I32Extend16S => Instruction::I32Extend16S,
             wasmparser::Operator::I32Const { value } => Instruction::I32Const(value),
+            wasmparser::Operator::I64Add => Instruction::I64Add,
+            wasmparser::Operator::I64Sub => Instruction::I64Sub,
+            wasmparser::Operator::I64Mul => Instruction::I64Mul,
+            wasmparser::Operator::I64DivS => Instruction::I64DivS,
+            wasmparser::Operator::I64DivU => Instruction::I64DivU,
+            wasmparser::Operator::I64RemS => Instruction::I64RemS,
+            wasmparser::Operator::I64RemU => Instruction::I64RemU,
+            wasmparser::Operator::I64And => Instruction::I64And,
+            wasmparser::Operator::I64Or => Instruction::I64Or,
+            wasmparser::Operator::I64Xor => Instruction::I64Xor,
+            wasmparser::Operator::I64Shl => Instruction::I64Shl,
+            wasmparser::Operator::I64ShrS => Instruction::I64ShrS,
+            wasmparser::Operator::I64ShrU => Instruction::I64ShrU,
+            wasmparser::Operator::I64Rotl => Instruction::I64Rotl,
+            wasmparser::Operator::I64Rotr => Instruction::I64Rotr,
+            wasmparser::Operator::I64Clz => Instruction::I64Clz,
+            wasmparser::Operator::I64Ctz => Instruction::I64Ctz,
+            wasmparser::Operator::I64Popcnt => Instruction::I64Popcnt,
+            wasmparser::Operator::I64Eqz => Instruction::I64Eqz,
+            wasmparser::Operator::I64Eq => Instruction::I64Eq,
+            wasmparser::Operator::I64Ne => Instruction::I64Ne,
+            wasmparser::Operator::I64LtS => Instruction::I64LtS,
+            wasmparser::Operator::I64LtU => Instruction::I64LtU,
+            wasmparser::Operator::I64GtS => Instruction::I64GtS,
+            wasmparser::Operator::I64GtU => Instruction::I64GtU,
+            wasmparser::Operator::I64LeS => Instruction::I64LeS,
+            wasmparser::Operator::I64LeU => Instruction::I64LeU,
+            wasmparser::Operator::I64GeS => Instruction::I64GeS,
+            wasmparser::Operator::I64GeU => Instruction::I64GeU,
+            wasmparser::Operator::I64Extend8S => Instruction::I64Extend8S,
+            wasmparser::Operator::I64Extend16S => Instruction::I64Extend16S,
+            wasmparser::Operator::I64Extend32S => Instruction::I64Extend32S,
+            wasmparser::Operator::I32WrapI64 => Instruction::I32WrapI64,
+            wasmparser::Operator::I64ExtendI32S => Instruction::I64ExtendI32S,
+            wasmparser::Operator::I64ExtendI32U => Instruction::I64ExtendI32U,
+            wasmparser::Operator::I64Const { value } => Instruction::I64Const(value),
+            wasmparser::Operator::F32Abs => Instruction::F32Abs,
+            wasmparser::Operator::F32Neg => Instruction::F32Neg,
+            wasmparser::Operator::F32Ceil => Instruction::F32Ceil,
+            wasmparser::Operator::F32Floor => Instruction::F32Floor,
+            wasmparser::Operator::F32Trunc => Instruction::F32Trunc,
+            wasmparser::Operator::F32Nearest => Instruction::F32Nearest,
+            wasmparser::Operator::F32Sqrt => Instruction::F32Sqrt,
+            wasmparser::Operator::F32Add => Instruction::F32Add,
+            wasmparser::Operator::F32Sub => Instruction::F32Sub,
+            wasmparser::Operator::F32Mul => Instruction::F32Mul,
+            wasmparser::Operator::F32Div => Instruction::F32Div,
+            wasmparser::Operator::F32Min => Instruction::F32Min,
+            wasmparser::Operator::F32Max => Instruction::F32Max,
+            wasmparser::Operator::F32Copysign => Instruction::F32Copysign,
+            wasmparser::Operator::F32Eq => Instruction::F32Eq,
+            wasmparser::Operator::F32Ne => Instruction::F32Ne,
+            wasmparser::Operator::F32Lt => Instruction::F32Lt,
+            wasmparser::Operator::F32Gt => Instruction::F32Gt,
+            wasmparser::Operator::F32Le => Instruction::F32Le,
+            wasmparser::Operator::F32Ge => Instruction::F32Ge,
+            wasmparser::Operator::F64Abs => Instruction::F64Abs,
+            wasmparser::Operator::F64Neg => Instruction::F64Neg,
+            wasmparser::Operator::F64Ceil => Instruction::F64Ceil,
+            wasmparser::Operator::F64Floor => Instruction::F64Floor,
+            wasmparser::Operator::F64Trunc => Instruction::F64Trunc,
+            wasmparser::Operator::F64Nearest => Instruction::F64Nearest,
+            wasmparser::Operator::F64Sqrt => Instruction::F64Sqrt,
+            wasmparser::Operator::F64Add => Instruction::F64Add,
+            wasmparser::Operator::F64Sub => Instruction::F64Sub,
+            wasmparser::Operator::F64Mul => Instruction::F64Mul,
+            wasmparser::Operator::F64Div => Instruction::F64Div,
+            wasmparser::Operator::F64Min => Instruction::F64Min,
+            wasmparser::Operator::F64Max => Instruction::F64Max,
+            wasmparser::Operator::F64Copysign => Instruction::F64Copysign,
+            wasmparser::Operator::F64Eq => Instruction::F64Eq,
+            wasmparser::Operator::F64Ne => Instruction::F64Ne,
+            wasmparser::Operator::F64Lt => Instruction::F64Lt,
+            wasmparser::Operator::F64Gt => Instruction::F64Gt,
+            wasmparser::Operator::F64Le => Instruction::F64Le,
+            wasmparser::Operator::F64Ge => Instruction::F64Ge,
+            wasmparser::Operator::F32Const { value } => Instruction::F32Const(value.bits()),
+            wasmparser::Operator::F64Const { value } => Instruction::F64Const(value.bits()),
+            wasmparser::Operator::I32TruncF32S => Instruction::I32TruncF32S,
+            wasmparser::Operator::I32TruncF32U => Instruction::I32TruncF32U,
+            wasmparser::Operator::I32TruncF64S => Instruction::I32TruncF64S,
+            wasmparser::Operator::I32TruncF64U => Instruction::I32TruncF64U,
+            wasmparser::Operator::I64TruncF32S => Instruction::I64TruncF32S,
+            wasmparser::Operator::I64TruncF32U => Instruction::I64TruncF32U,
+            wasmparser::Operator::I64TruncF64S => Instruction::I64TruncF64S,
+            wasmparser::Operator::I64TruncF64U => Instruction::I64TruncF64U,
+            wasmparser::Operator::I32TruncSatF32S => Instruction::I32TruncSatF32S,
+            wasmparser::Operator::I32TruncSatF32U => Instruction::I32TruncSatF32U,
+            wasmparser::Operator::I32TruncSatF64S => Instruction::I32TruncSatF64S,
+            wasmparser::Operator::I32TruncSatF64U => Instruction::I32TruncSatF64U,
+            wasmparser::Operator::I64TruncSatF32S => Instruction::I64TruncSatF32S,
+            wasmparser::Operator::I64TruncSatF32U => Instruction::I64TruncSatF32U,
+            wasmparser::Operator::I64TruncSatF64S => Instruction::I64TruncSatF64S,
+            wasmparser::Operator::I64TruncSatF64U => Instruction::I64TruncSatF64U,
+            wasmparser::Operator::F32ConvertI32S => Instruction::F32ConvertI32S,
+            wasmparser::Operator::F32ConvertI32U => Instruction::F32ConvertI32U,
+            wasmparser::Operator::F32ConvertI64S => Instruction::F32ConvertI64S,
+            wasmparser::Operator::F32ConvertI64U => Instruction::F32ConvertI64U,
+            wasmparser::Operator::F32DemoteF64 => Instruction::F32DemoteF64,
+            wasmparser::Operator::F64ConvertI32S => Instruction::F64ConvertI32S,
+            wasmparser::Operator::F64ConvertI32U => Instruction::F64ConvertI32U,
+            wasmparser::Operator::F64ConvertI64S => Instruction::F64ConvertI64S,
+            wasmparser::Operator::F64ConvertI64U => Instruction::F64ConvertI64U,
+            wasmparser::Operator::F64PromoteF32 => Instruction::F64PromoteF32,
+            wasmparser::Operator::I32ReinterpretF32 => Instruction::I32ReinterpretF32,
+            wasmparser::Operator::I64ReinterpretF64 => Instruction::I64ReinterpretF64,
+            wasmparser::Operator::F32ReinterpretI32 => Instruction::F32ReinterpretI32,
+            wasmparser::Operator::F64ReinterpretI64 => Instruction::F64ReinterpretI64,
+            wasmparser::Operator::V128Load { memarg } => Instruction::V128Load(memarg.into()),
+            wasmparser::Operator::V128Load8x8S { memarg } => Instruction::V128Load8x8S(memarg.into()),
+            wasmparser::Operator::V128Load8x8U { memarg } => Instruction::V128Load8x8U(memarg.into()),
+            wasmparser::Operator::V128Load16x4S { memarg } => Instruction::V128Load16x4S(memarg.into()),
+            wasmparser::Operator::V128Load16x4U { memarg } => Instruction::V128Load16x4U(memarg.into()),
+            wasmparser::Operator::V128Load32x2S { memarg } => Instruction::V128Load32x2S(memarg.into()),
+            wasmparser::Operator::V128Load32x2U { memarg } => Instruction::V128Load32x2U(memarg.into()),
+            wasmparser::Operator::V128Load8Splat { memarg } => Instruction::V128Load8Splat(memarg.into()),
+            wasmparser::Operator::V128Load16Splat { memarg } => Instruction::V128Load16Splat(memarg.into()),
+            wasmparser::Operator::V128Load32Splat { memarg } => Instruction::V128Load32Splat(memarg.into()),
+            wasmparser::Operator::V128Load64Splat { memarg } => Instruction::V128Load64Splat(memarg.into()),
+            wasmparser::Operator::V128Load32Zero { memarg } => Instruction::V128Load32Zero(memarg.into()),
+            wasmparser::Operator::V128Load64Zero { memarg } => Instruction::V128Load64Zero(memarg.into()),
+            wasmparser::Operator::V128Store { memarg } => Instruction::V128Store(memarg.into()),
+            wasmparser::Operator::V128Load8Lane { memarg, lane } => Instruction::V128Load8Lane { memarg: memarg.into(), lane },
+            wasmparser::Operator::V128Load16Lane { memarg, lane } => Instruction::V128Load16Lane { memarg: memarg.into(), lane },
+            wasmparser::Operator::V128Load32Lane { memarg, lane } => Instruction::V128Load32Lane { memarg: memarg.into(), lane },
+            wasmparser::Operator::V128Load64Lane { memarg, lane } => Instruction::V128Load64Lane { memarg: memarg.into(), lane },
+            wasmparser::Operator::V128Store8Lane { memarg, lane } => Instruction::V128Store8Lane { memarg: memarg.into(), lane },
+            wasmparser::Operator::V128Store16Lane { memarg, lane } => Instruction::V128Store16Lane { memarg: memarg.into(), lane },
+            wasmparser::Operator::V128Store32Lane { memarg, lane } => Instruction::V128Store32Lane { memarg: memarg.into(), lane },
+            wasmparser::Operator::V128Store64Lane { memarg, lane } => Instruction::V128Store64Lane { memarg: memarg.into(), lane },
+            wasmparser::Operator::V128Const { value } => Instruction::V128Const(u128::from_le_bytes(*value.bytes())),
+            wasmparser::Operator::I8x16Shuffle { lanes } => Instruction::I8x16Shuffle(lanes),
+            wasmparser::Operator::I8x16ExtractLaneS { lane } => Instruction::I8x16ExtractLaneS(lane),
+            wasmparser::Operator::I8x16ExtractLaneU { lane } => Instruction::I8x16ExtractLaneU(lane),
+            wasmparser::Operator::I8x16ReplaceLane { lane } => Instruction::I8x16ReplaceLane(lane),
+            wasmparser::Operator::I16x8ExtractLaneS { lane } => Instruction::I16x8ExtractLaneS(lane),
+            wasmparser::Operator::I16x8ExtractLaneU { lane } => Instruction::I16x8ExtractLaneU(lane),
+            wasmparser::Operator::I16x8ReplaceLane { lane } => Instruction::I16x8ReplaceLane(lane),
+            wasmparser::Operator::I32x4ExtractLane { lane } => Instruction::I32x4ExtractLane(lane),
+            wasmparser::Operator::I32x4ReplaceLane { lane } => Instruction::I32x4ReplaceLane(lane),
+            wasmparser::Operator::I64x2ExtractLane { lane } => Instruction::I64x2ExtractLane(lane),
+            wasmparser::Operator::I64x2ReplaceLane { lane } => Instruction::I64x2ReplaceLane(lane),
+            wasmparser::Operator::F32x4ExtractLane { lane } => Instruction::F32x4ExtractLane(lane),
+            wasmparser::Operator::F32x4ReplaceLane { lane } => Instruction::F32x4ReplaceLane(lane),
+            wasmparser::Operator::F64x2ExtractLane { lane } => Instruction::F64x2ExtractLane(lane),
+            wasmparser::Operator::F64x2ReplaceLane { lane } => Instruction::F64x2ReplaceLane(lane),
+            wasmparser::Operator::I8x16Swizzle => Instruction::I8x16Swizzle,
+            wasmparser::Operator::I8x16Splat => Instruction::I8x16Splat,
+            wasmparser::Operator::I16x8Splat => Instruction::I16x8Splat,
+            wasmparser::Operator::I32x4Splat => Instruction::I32x4Splat,
+            wasmparser::Operator::I64x2Splat => Instruction::I64x2Splat,
+            wasmparser::Operator::F32x4Splat => Instruction::F32x4Splat,
+            wasmparser::Operator::F64x2Splat => Instruction::F64x2Splat,
+            wasmparser::Operator::I8x16Eq => Instruction::I8x16Eq,
+            wasmparser::Operator::I8x16Ne => Instruction::I8x16Ne,
+            wasmparser::Operator::I8x16LtS => Instruction::I8x16LtS,
+            wasmparser::Operator::I8x16LtU => Instruction::I8x16LtU,
+            wasmparser::Operator::I8x16GtS => Instruction::I8x16GtS,
+            wasmparser::Operator::I8x16GtU => Instruction::I8x16GtU,
+            wasmparser::Operator::I8x16LeS => Instruction::I8x16LeS,
+            wasmparser::Operator::I8x16LeU => Instruction::I8x16LeU,
+            wasmparser::Operator::I8x16GeS => Instruction::I8x16GeS,
+            wasmparser::Operator::I8x16GeU => Instruction::I8x16GeU,
+            wasmparser::Operator::I16x8Eq => Instruction::I16x8Eq,
+            wasmparser::Operator::I16x8Ne => Instruction::I16x8Ne,
+            wasmparser::Operator::I16x8LtS => Instruction::I16x8LtS,
+            wasmparser::Operator::I16x8LtU => Instruction::I16x8LtU,
+            wasmparser::Operator::I16x8GtS => Instruction::I16x8GtS,
+            wasmparser::Operator::I16x8GtU => Instruction::I16x8GtU,
+            wasmparser::Operator::I16x8LeS => Instruction::I16x8LeS,
+            wasmparser::Operator::I16x8LeU => Instruction::I16x8LeU,
+            wasmparser::Operator::I16x8GeS => Instruction::I16x8GeS,
+            wasmparser::Operator::I16x8GeU => Instruction::I16x8GeU,
+            wasmparser::Operator::I32x4Eq => Instruction::I32x4Eq,
+            wasmparser::Operator::I32x4Ne => Instruction::I32x4Ne,
+            wasmparser::Operator::I32x4LtS => Instruction::I32x4LtS,
+            wasmparser::Operator::I32x4LtU => Instruction::I32x4LtU,
+            wasmparser::Operator::I32x4GtS => Instruction::I32x4GtS,
+            wasmparser::Operator::I32x4GtU => Instruction::I32x4GtU,
+            wasmparser::Operator::I32x4LeS => Instruction::I32x4LeS,
+            wasmparser::Operator::I32x4LeU => Instruction::I32x4LeU,
+            wasmparser::Operator::I32x4GeS => Instruction::I32x4GeS,
+            wasmparser::Operator::I32x4GeU => Instruction::I32x4GeU,
+            wasmparser::Operator::I64x2Eq => Instruction::I64x2Eq,
+            wasmparser::Operator::I64x2Ne => Instruction::I64x2Ne,
+            wasmparser::Operator::I64x2LtS => Instruction::I64x2LtS,
+            wasmparser::Operator::I64x2GtS => Instruction::I64x2GtS,
+            wasmparser::Operator::I64x2LeS => Instruction::I64x2LeS,
+            wasmparser::Operator::I64x2GeS => Instruction::I64x2GeS,
+            wasmparser::Operator::F32x4Eq => Instruction::F32x4Eq,
+            wasmparser::Operator::F32x4Ne => Instruction::F32x4Ne,
+            wasmparser::Operator::F32x4Lt => Instruction::F32x4Lt,
+            wasmparser::Operator::F32x4Gt => Instruction::F32x4Gt,
+            wasmparser::Operator::F32x4Le => Instruction::F32x4Le,
+            wasmparser::Operator::F32x4Ge => Instruction::F32x4Ge,
+            wasmparser::Operator::F64x2Eq => Instruction::F64x2Eq,
+            wasmparser::Operator::F64x2Ne => Instruction::F64x2Ne,
+            wasmparser::Operator::F64x2Lt => Instruction::F64x2Lt,
+            wasmparser::Operator::F64x2Gt => Instruction::F64x2Gt,
+            wasmparser::Operator::F64x2Le => Instruction::F64x2Le,
+            wasmparser::Operator::F64x2Ge => Instruction::F64x2Ge,
+            wasmparser::Operator::V128Not => Instruction::V128Not,
+            wasmparser::Operator::V128And => Instruction::V128And,
+            wasmparser::Operator::V128AndNot => Instruction::V128AndNot,
+            wasmparser::Operator::V128Or => Instruction::V128Or,
+            wasmparser::Operator::V128Xor => Instruction::V128Xor,
+            wasmparser::Operator::V128Bitselect => Instruction::V128Bitselect,
+            wasmparser::Operator::V128AnyTrue => Instruction::V128AnyTrue,
+            wasmparser::Operator::I8x16Abs => Instruction::I8x16Abs,
+            wasmparser::Operator::I8x16Neg => Instruction::I8x16Neg,
+            wasmparser::Operator::I8x16Popcnt => Instruction::I8x16Popcnt,
+            wasmparser::Operator::I8x16AllTrue => Instruction::I8x16AllTrue,
+            wasmparser::Operator::I8x16Bitmask => Instruction::I8x16Bitmask,
+            wasmparser::Operator::I8x16NarrowI16x8S => Instruction::I8x16NarrowI16x8S,
+            wasmparser::Operator::I8x16NarrowI16x8U => Instruction::I8x16NarrowI16x8U,
+            wasmparser::Operator::I8x16Shl => Instruction::I8x16Shl,
+            wasmparser::Operator::I8x16ShrS => Instruction::I8x16ShrS,
+            wasmparser::Operator::I8x16ShrU => Instruction::I8x16ShrU,
+            wasmparser::Operator::I8x16Add => Instruction::I8x16Add,
+            wasmparser::Operator::I8x16AddSatS => Instruction::I8x16AddSatS,
+            wasmparser::Operator::I8x16AddSatU => Instruction::I8x16AddSatU,
+            wasmparser::Operator::I8x16Sub => Instruction::I8x16Sub,
+            wasmparser::Operator::I8x16SubSatS => Instruction::I8x16SubSatS,
+            wasmparser::Operator::I8x16SubSatU => Instruction::I8x16SubSatU,
+            wasmparser::Operator::I8x16MinS => Instruction::I8x16MinS,
+            wasmparser::Operator::I8x16MinU => Instruction::I8x16MinU,
+            wasmparser::Operator::I8x16MaxS => Instruction::I8x16MaxS,
+            wasmparser::Operator::I8x16MaxU => Instruction::I8x16MaxU,
+            wasmparser::Operator::I8x16AvgrU => Instruction::I8x16AvgrU,
+            wasmparser::Operator::I16x8ExtAddPairwiseI8x16S => Instruction::I16x8ExtAddPairwiseI8x16S,
+            wasmparser::Operator::I16x8ExtAddPairwiseI8x16U => Instruction::I16x8ExtAddPairwiseI8x16U,
+            wasmparser::Operator::I16x8Abs => Instruction::I16x8Abs,
+            wasmparser::Operator::I16x8Neg => Instruction::I16x8Neg,
+            wasmparser::Operator::I16x8Q15MulrSatS => Instruction::I16x8Q15MulrSatS,
+            wasmparser::Operator::I16x8AllTrue => Instruction::I16x8AllTrue,
+            wasmparser::Operator::I16x8Bitmask => Instruction::I16x8Bitmask,
+            wasmparser::Operator::I16x8NarrowI32x4S => Instruction::I16x8NarrowI32x4S,
+            wasmparser::Operator::I16x8NarrowI32x4U => Instruction::I16x8NarrowI32x4U,
+            wasmparser::Operator::I16x8ExtendLowI8x16S => Instruction::I16x8ExtendLowI8x16S,
+            wasmparser::Operator::I16x8ExtendHighI8x16S => Instruction::I16x8ExtendHighI8x16S,
+            wasmparser::Operator::I16x8ExtendLowI8x16U => Instruction::I16x8ExtendLowI8x16U,
+            wasmparser::Operator::I16x8ExtendHighI8x16U => Instruction::I16x8ExtendHighI8x16U,
+            wasmparser::Operator::I16x8Shl => Instruction::I16x8Shl,
+            wasmparser::Operator::I16x8ShrS => Instruction::I16x8ShrS,
+            wasmparser::Operator::I16x8ShrU => Instruction::I16x8ShrU,
+            wasmparser::Operator::I16x8Add => Instruction::I16x8Add,
+            wasmparser::Operator::I16x8AddSatS => Instruction::I16x8AddSatS,
+            wasmparser::Operator::I16x8AddSatU => Instruction::I16x8AddSatU,
+            wasmparser::Operator::I16x8Sub => Instruction::I16x8Sub,
+            wasmparser::Operator::I16x8SubSatS => Instruction::I16x8SubSatS,
+            wasmparser::Operator::I16x8SubSatU => Instruction::I16x8SubSatU,
+            wasmparser::Operator::I16x8Mul => Instruction::I16x8Mul,
+            wasmparser::Operator::I16x8MinS => Instruction::I16x8MinS,
+            wasmparser::Operator::I16x8MinU => Instruction::I16x8MinU,
+            wasmparser::Operator::I16x8MaxS => Instruction::I16x8MaxS,
+            wasmparser::Operator::I16x8MaxU => Instruction::I16x8MaxU,
+            wasmparser::Operator::I16x8AvgrU => Instruction::I16x8AvgrU,
+            wasmparser::Operator::I16x8ExtMulLowI8x16S => Instruction::I16x8ExtMulLowI8x16S,
+            wasmparser::Operator::I16x8ExtMulHighI8x16S => Instruction::I16x8ExtMulHighI8x16S,
+            wasmparser::Operator::I16x8ExtMulLowI8x16U => Instruction::I16x8ExtMulLowI8x16U,
+            wasmparser::Operator::I16x8ExtMulHighI8x16U => Instruction::I16x8ExtMulHighI8x16U,
+            wasmparser::Operator::I32x4ExtAddPairwiseI16x8S => Instruction::I32x4ExtAddPairwiseI16x8S,
+            wasmparser::Operator::I32x4ExtAddPairwiseI16x8U => Instruction::I32x4ExtAddPairwiseI16x8U,
+            wasmparser::Operator::I32x4Abs => Instruction::I32x4Abs,
+            wasmparser::Operator::I32x4Neg => Instruction::I32x4Neg,
+            wasmparser::Operator::I32x4AllTrue => Instruction::I32x4AllTrue,
+            wasmparser::Operator::I32x4Bitmask => Instruction::I32x4Bitmask,
+            wasmparser::Operator::I32x4ExtendLowI16x8S => Instruction::I32x4ExtendLowI16x8S,
+            wasmparser::Operator::I32x4ExtendHighI16x8S => Instruction::I32x4ExtendHighI16x8S,
+            wasmparser::Operator::I32x4ExtendLowI16x8U => Instruction::I32x4ExtendLowI16x8U,
+            wasmparser::Operator::I32x4ExtendHighI16x8U => Instruction::I32x4ExtendHighI16x8U,
+            wasmparser::Operator::I32x4Shl => Instruction::I32x4Shl,
+            wasmparser::Operator::I32x4ShrS => Instruction::I32x4ShrS,
+            wasmparser::Operator::I32x4ShrU => Instruction::I32x4ShrU,
+            wasmparser::Operator::I32x4Add => Instruction::I32x4Add,
+            wasmparser::Operator::I32x4Sub => Instruction::I32x4Sub,
+            wasmparser::Operator::I32x4Mul => Instruction::I32x4Mul,
+            wasmparser::Operator::I32x4MinS => Instruction::I32x4MinS,
+            wasmparser::Operator::I32x4MinU => Instruction::I32x4MinU,
+            wasmparser::Operator::I32x4MaxS => Instruction::I32x4MaxS,
+            wasmparser::Operator::I32x4MaxU => Instruction::I32x4MaxU,
+            wasmparser::Operator::I32x4DotI16x8S => Instruction::I32x4DotI16x8S,
+            wasmparser::Operator::I32x4ExtMulLowI16x8S => Instruction::I32x4ExtMulLowI16x8S,
+            wasmparser::Operator::I32x4ExtMulHighI16x8S => Instruction::I32x4ExtMulHighI16x8S,
+            wasmparser::Operator::I32x4ExtMulLowI16x8U => Instruction::I32x4ExtMulLowI16x8U,
+            wasmparser::Operator::I32x4ExtMulHighI16x8U => Instruction::I32x4ExtMulHighI16x8U,
+            wasmparser::Operator::I64x2Abs => Instruction::I64x2Abs,
+            wasmparser::Operator::I64x2Neg => Instruction::I64x2Neg,
+            wasmparser::Operator::I64x2AllTrue => Instruction::I64x2AllTrue,
+            wasmparser::Operator::I64x2Bitmask => Instruction::I64x2Bitmask,
+            wasmparser::Operator::I64x2ExtendLowI32x4S => Instruction::I64x2ExtendLowI32x4S,
+            wasmparser::Operator::I64x2ExtendHighI32x4S => Instruction::I64x2ExtendHighI32x4S,
+            wasmparser::Operator::I64x2ExtendLowI32x4U => Instruction::I64x2ExtendLowI32x4U,
+            wasmparser::Operator::I64x2ExtendHighI32x4U => Instruction::I64x2ExtendHighI32x4U,
+            wasmparser::Operator::I64x2Shl => Instruction::I64x2Shl,
+            wasmparser::Operator::I64x2ShrS => Instruction::I64x2ShrS,
+            wasmparser::Operator::I64x2ShrU => Instruction::I64x2ShrU,
+            wasmparser::Operator::I64x2Add => Instruction::I64x2Add,
+            wasmparser::Operator::I64x2Sub => Instruction::I64x2Sub,
+            wasmparser::Operator::I64x2Mul => Instruction::I64x2Mul,
+            wasmparser::Operator::I64x2ExtMulLowI32x4S => Instruction::I64x2ExtMulLowI32x4S,
+            wasmparser::Operator::I64x2ExtMulHighI32x4S => Instruction::I64x2ExtMulHighI32x4S,
+            wasmparser::Operator::I64x2ExtMulLowI32x4U => Instruction::I64x2ExtMulLowI32x4U,
+            wasmparser::Operator::I64x2ExtMulHighI32x4U => Instruction::I64x2ExtMulHighI32x4U,
+            wasmparser::Operator::F32x4Ceil => Instruction::F32x4Ceil,
+            wasmparser::Operator::F32x4Floor => Instruction::F32x4Floor,
+            wasmparser::Operator::F32x4Trunc => Instruction::F32x4Trunc,
+            wasmparser::Operator::F32x4Nearest => Instruction::F32x4Nearest,
+            wasmparser::Operator::F32x4Abs => Instruction::F32x4Abs,
+            wasmparser::Operator::F32x4Neg => Instruction::F32x4Neg,
+            wasmparser::Operator::F32x4Sqrt => Instruction::F32x4Sqrt,
+            wasmparser::Operator::F32x4Add => Instruction::F32x4Add,
+            wasmparser::Operator::F32x4Sub => Instruction::F32x4Sub,
+            wasmparser::Operator::F32x4Mul => Instruction::F32x4Mul,
+            wasmparser::Operator::F32x4Div => Instruction::F32x4Div,
+            wasmparser::Operator::F32x4Min => Instruction::F32x4Min,
+            wasmparser::Operator::F32x4Max => Instruction::F32x4Max,
+            wasmparser::Operator::F32x4PMin => Instruction::F32x4PMin,
+            wasmparser::Operator::F32x4PMax => Instruction::F32x4PMax,
+            wasmparser::Operator::F64x2Ceil => Instruction::F64x2Ceil,
+            wasmparser::Operator::F64x2Floor => Instruction::F64x2Floor,
+            wasmparser::Operator::F64x2Trunc => Instruction::F64x2Trunc,
+            wasmparser::Operator::F64x2Nearest => Instruction::F64x2Nearest,
+            wasmparser::Operator::F64x2Abs => Instruction::F64x2Abs,
+            wasmparser::Operator::F64x2Neg => Instruction::F64x2Neg,
+            wasmparser::Operator::F64x2Sqrt => Instruction::F64x2Sqrt,
+            wasmparser::Operator::F64x2Add => Instruction::F64x2Add,
+            wasmparser::Operator::F64x2Sub => Instruction::F64x2Sub,
+            wasmparser::Operator::F64x2Mul => Instruction::F64x2Mul,
+            wasmparser::Operator::F64x2Div => Instruction::F64x2Div,
+            wasmparser::Operator::F64x2Min => Instruction::F64x2Min,
+            wasmparser::Operator::F64x2Max => Instruction::F64x2Max,
+            wasmparser::Operator::F64x2PMin => Instruction::F64x2PMin,
+            wasmparser::Operator::F64x2PMax => Instruction::F64x2PMax,
+            wasmparser::Operator::I32x4TruncSatF32x4S => Instruction::I32x4TruncSatF32x4S,
+            wasmparser::Operator::I32x4TruncSatF32x4U => Instruction::I32x4TruncSatF32x4U,
+            wasmparser::Operator::F32x4ConvertI32x4S => Instruction::F32x4ConvertI32x4S,
+            wasmparser::Operator::F32x4ConvertI32x4U => Instruction::F32x4ConvertI32x4U,
+            wasmparser::Operator::I32x4TruncSatF64x2SZero => Instruction::I32x4TruncSatF64x2SZero,
+            wasmparser::Operator::I32x4TruncSatF64x2UZero => Instruction::I32x4TruncSatF64x2UZero,
+            wasmparser::Operator::F64x2ConvertLowI32x4S => Instruction::F64x2ConvertLowI32x4S,
+            wasmparser::Operator::F64x2ConvertLowI32x4U => Instruction::F64x2ConvertLowI32x4U,
+            wasmparser::Operator::F32x4DemoteF64x2Zero => Instruction::F32x4DemoteF64x2Zero,
+            wasmparser::Operator::F64x2PromoteLowF32x4 => Instruction::F64x2PromoteLowF32x4,
+            wasmparser::Operator::I8x16RelaxedSwizzle => Instruction::I8x16RelaxedSwizzle,
+            wasmparser::Operator::I32x4RelaxedTruncF32x4S => Instruction::I32x4RelaxedTruncF32x4S,
+            wasmparser::Operator::I32x4RelaxedTruncF32x4U => Instruction::I32x4RelaxedTruncF32x4U,
+            wasmparser::Operator::I32x4RelaxedTruncF64x2SZero => Instruction::I32x4RelaxedTruncF64x2SZero,
+            wasmparser::Operator::I32x4RelaxedTruncF64x2UZero => Instruction::I32x4RelaxedTruncF64x2UZero,
+            wasmparser::Operator::F32x4RelaxedMadd => Instruction::F32x4RelaxedMadd,
+            wasmparser::Operator::F32x4RelaxedNmadd => Instruction::F32x4RelaxedNmadd,
+            wasmparser::Operator::F64x2RelaxedMadd => Instruction::F64x2RelaxedMadd,
+            wasmparser::Operator::F64x2RelaxedNmadd => Instruction::F64x2RelaxedNmadd,
+            wasmparser::Operator::I8x16RelaxedLaneselect => Instruction::I8x16RelaxedLaneselect,
+            wasmparser::Operator::I16x8RelaxedLaneselect => Instruction::I16x8RelaxedLaneselect,
+            wasmparser::Operator::I32x4RelaxedLaneselect => Instruction::I32x4RelaxedLaneselect,
+            wasmparser::Operator::I64x2RelaxedLaneselect => Instruction::I64x2RelaxedLaneselect,
+            wasmparser::Operator::F32x4RelaxedMin => Instruction::F32x4RelaxedMin,
+            wasmparser::Operator::F32x4RelaxedMax => Instruction::F32x4RelaxedMax,
+            wasmparser::Operator::F64x2RelaxedMin => Instruction::F64x2RelaxedMin,
+            wasmparser::Operator::F64x2RelaxedMax => Instruction::F64x2RelaxedMax,
+            wasmparser::Operator::I16x8RelaxedQ15mulrS => Instruction::I16x8RelaxedQ15mulrS,
+            wasmparser::Operator::I16x8RelaxedDotI8x16I7x16S => Instruction::I16x8RelaxedDotI8x16I7x16S,
+            wasmparser::Operator::I32x4RelaxedDotI8x16I7x16AddS => Instruction::I32x4RelaxedDotI8x16I7x16AddS,
             wasmparser::Operator::LocalGet { local_index } => Instruction::LocalGet(local_index),
             wasmparser::Operator::LocalSet { local_index } => Instruction::LocalSet(local_index),
+            wasmparser::Operator::LocalTee { local_index } => Instruction::LocalTee(local_index),
+            wasmparser::Operator::Unreachable => Instruction::Unreachable,
+            wasmparser::Operator::Nop => Instruction::Nop,
+            wasmparser::Operator::Drop => Instruction::Drop,
+            wasmparser::Operator::Select => Instruction::Select,
+            wasmparser::Operator::TypedSelect { ty } => {
+                ValType::try_from(ty)?;
+                Instruction::Select
+            },
+            wasmparser::Operator::GlobalGet { global_index } => Instruction::GlobalGet(global_index),
+            wasmparser::Operator::GlobalSet { global_index } => Instruction::GlobalSet(global_index),
+            wasmparser::Operator::I32Load { memarg } => Instruction::I32Load(memarg.into()),
+            wasmparser::Operator::I64Load { memarg } => Instruction::I64Load(memarg.into()),
+            wasmparser::Operator::F32Load { memarg } => Instruction::F32Load(memarg.into()),
+            wasmparser::Operator::F64Load { memarg } => Instruction::F64Load(memarg.into()),
+            wasmparser::Operator::I32Load8S { memarg } => Instruction::I32Load8S(memarg.into()),
+            wasmparser::Operator::I32Load8U { memarg } => Instruction::I32Load8U(memarg.into()),
+            wasmparser::Operator::I32Load16S { memarg } => Instruction::I32Load16S(memarg.into()),
+            wasmparser::Operator::I32Load16U { memarg } => Instruction::I32Load16U(memarg.into()),
+            wasmparser::Operator::I64Load8S { memarg } => Instruction::I64Load8S(memarg.into()),
+            wasmparser::Operator::I64Load8U { memarg } => Instruction::I64Load8U(memarg.into()),
+            wasmparser::Operator::I64Load16S { memarg } => Instruction::I64Load16S(memarg.into()),
+            wasmparser::Operator::I64Load16U { memarg } => Instruction::I64Load16U(memarg.into()),
+            wasmparser::Operator::I64Load32S { memarg } => Instruction::I64Load32S(memarg.into()),
+            wasmparser::Operator::I64Load32U { memarg } => Instruction::I64Load32U(memarg.into()),
+            wasmparser::Operator::I32Store { memarg } => Instruction::I32Store(memarg.into()),
+            wasmparser::Operator::I64Store { memarg } => Instruction::I64Store(memarg.into()),
+            wasmparser::Operator::F32Store { memarg } => Instruction::F32Store(memarg.into()),
+            wasmparser::Operator::F64Store { memarg } => Instruction::F64Store(memarg.into()),
+            wasmparser::Operator::I32Store8 { memarg } => Instruction::I32Store8(memarg.into()),
+            wasmparser::Operator::I32Store16 { memarg } => Instruction::I32Store16(memarg.into()),
+            wasmparser::Operator::I64Store8 { memarg } => Instruction::I64Store8(memarg.into()),
+            wasmparser::Operator::I64Store16 { memarg } => Instruction::I64Store16(memarg.into()),
+            wasmparser::Operator::I64Store32 { memarg } => Instruction::I64Store32(memarg.into()),
+            wasmparser::Operator::MemorySize { mem } => Instruction::MemorySize(mem),
+            wasmparser::Operator::MemoryGrow { mem } => Instruction::MemoryGrow(mem),
+            wasmparser::Operator::MemoryInit { data_index, mem } => Instruction::MemoryInit { data: data_index, memory: mem },
+            wasmparser::Operator::DataDrop { data_index } => Instruction::DataDrop(data_index),
+            wasmparser::Operator::MemoryCopy { dst_mem, src_mem } => Instruction::MemoryCopy { destination: dst_mem, source: src_mem },
+            wasmparser::Operator::MemoryFill { mem } => Instruction::MemoryFill(mem),
+            wasmparser::Operator::TableGet { table } => Instruction::TableGet(table),
+            wasmparser::Operator::TableSet { table } => Instruction::TableSet(table),
+            wasmparser::Operator::TableSize { table } => Instruction::TableSize(table),
+            wasmparser::Operator::TableGrow { table } => Instruction::TableGrow(table),
+            wasmparser::Operator::TableFill { table } => Instruction::TableFill(table),
+            wasmparser::Operator::TableCopy { dst_table, src_table } => Instruction::TableCopy { destination: dst_table, source: src_table },
+            wasmparser::Operator::TableInit { elem_index, table } => Instruction::TableInit { element: elem_index, table },
+            wasmparser::Operator::ElemDrop { elem_index } => Instruction::ElemDrop(elem_index),
+            wasmparser::Operator::RefNull { .. } => Instruction::RefNull,
+            wasmparser::Operator::RefIsNull => Instruction::RefIsNull,
+            wasmparser::Operator::RefFunc { function_index } => Instruction::RefFunc(function_index),
+            wasmparser::Operator::Throw { tag_index } => Instruction::Throw(tag_index),
+            wasmparser::Operator::ThrowRef => Instruction::ThrowRef,
+            wasmparser::Operator::Br { relative_depth } => Instruction::Br(relative_depth),
+            wasmparser::Operator::BrIf { relative_depth } => Instruction::BrIf(relative_depth),
             wasmparser::Operator::End => Instruction::End,
             wasmparser::Operator::Return => Instruction::Return,
             wasmparser::Operator::Call{function_index} => Instruction::Call(function_index),
-            _ => todo!("Operator {:?} not implemented yet", operator),
-        }
+            wasmparser::Operator::CallIndirect { type_index, table_index } => Instruction::CallIndirect { type_index, table: table_index },
+            wasmparser::Operator::ReturnCall { function_index } => Instruction::ReturnCall(function_index),
+            wasmparser::Operator::ReturnCallIndirect { type_index, table_index } => Instruction::ReturnCallIndirect { type_index, table: table_index },
+            _ => return Err(anyhow::anyhow!("Operator {:?} not implemented yet", operator)),
+        };
+        Ok(instruction)
     }
 }
 
@@ -79,12 +1011,14 @@ impl FuncType {
     }
 }
 
-impl From<wasmparser::FuncType> for FuncType {
-    fn from(func_type: wasmparser::FuncType) -> Self {
-        Self::new(
-            func_type.params().iter().map(|&param| param.into()).collect(),
-            func_type.results().iter().map(|&result| result.into()).collect()
-        )
+impl TryFrom<wasmparser::FuncType> for FuncType {
+    type Error = anyhow::Error;
+
+    fn try_from(func_type: wasmparser::FuncType) -> Result<Self> {
+        Ok(Self::new(
+            func_type.params().iter().map(|&param| param.try_into()).collect::<Result<_>>()?,
+            func_type.results().iter().map(|&result| result.try_into()).collect::<Result<_>>()?
+        ))
     }
 }
 
@@ -134,15 +1068,209 @@ impl Function {
     }
 
     fn add_instruction(&mut self, instruction: Instruction) {
+        self.definition_mut().body.push(instruction);
+    }
+
+    fn definition_mut(&mut self) -> &mut FunctionDefinition {
         match self.kind {
-            FunctKind::Definition(ref mut function_definition) => {
-                function_definition.body.push(instruction);
-            },
+            FunctKind::Definition(ref mut function_definition) => function_definition,
             _ => panic!("Cannot add instruction to import function"),
         }
     }
 }
 
+// Constant expressions are kept as instructions and evaluated on instantiation.
+#[derive(Debug)]
+struct ConstExpr(Vec<Instruction>);
+
+impl<'a> TryFrom<wasmparser::ConstExpr<'a>> for ConstExpr {
+    type Error = anyhow::Error;
+
+    fn try_from(const_expr: wasmparser::ConstExpr<'a>) -> Result<Self> {
+        let mut instructions = Vec::new();
+        for operator in const_expr.get_operators_reader().into_iter() {
+            match operator? {
+                wasmparser::Operator::End => break,
+                operator => instructions.push(operator.try_into()?),
+            }
+        }
+        Ok(Self(instructions))
+    }
+}
+
+// Globals
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct GlobalType {
+    val_type: ValType,
+    mutable: bool,
+}
+
+impl TryFrom<wasmparser::GlobalType> for GlobalType {
+    type Error = anyhow::Error;
+
+    fn try_from(global_type: wasmparser::GlobalType) -> Result<Self> {
+        Ok(Self { val_type: global_type.content_type.try_into()?, mutable: global_type.mutable })
+    }
+}
+
+// Imported globals are looked up in the `Imports` given at instantiation.
+#[derive(Debug)]
+enum GlobalInit {
+    Import { module: String, name: String },
+    ConstExpr(ConstExpr),
+}
+
+#[derive(Debug)]
+struct Global {
+    global_type: GlobalType,
+    init: GlobalInit,
+}
+
+// Memories and Data Segments
+#[derive(Debug)]
+struct MemoryType {
+    initial: u32,
+    maximum: Option<u32>,
+}
+
+impl TryFrom<wasmparser::MemoryType> for MemoryType {
+    type Error = anyhow::Error;
+
+    fn try_from(memory_type: wasmparser::MemoryType) -> Result<Self> {
+        if memory_type.memory64 || memory_type.shared || memory_type.page_size_log2.is_some() {
+            return Err(anyhow::anyhow!("Unsupported memory type {:?}", memory_type));
+        }
+        Ok(Self { initial: memory_type.initial as u32, maximum: memory_type.maximum.map(|maximum| maximum as u32) })
+    }
+}
+
+#[derive(Debug)]
+enum DataKind {
+    Active { memory: u32, offset: ConstExpr },
+    Passive,
+}
+
+#[derive(Debug)]
+struct DataSegment {
+    kind: DataKind,
+    data: Vec<u8>,
+}
+
+impl<'a> TryFrom<wasmparser::Data<'a>> for DataSegment {
+    type Error = anyhow::Error;
+
+    fn try_from(data: wasmparser::Data<'a>) -> Result<Self> {
+        let kind = match data.kind {
+            wasmparser::DataKind::Active { memory_index, offset_expr } => {
+                DataKind::Active { memory: memory_index, offset: offset_expr.try_into()? }
+            },
+            wasmparser::DataKind::Passive => DataKind::Passive,
+        };
+        Ok(Self { kind, data: data.data.to_vec() })
+    }
+}
+
+// Tables and Element Segments
+#[derive(Debug)]
+struct TableType {
+    element_type: ValType,
+    initial: u32,
+    maximum: Option<u32>,
+    init: Option<ConstExpr>,
+}
+
+impl<'a> TryFrom<wasmparser::Table<'a>> for TableType {
+    type Error = anyhow::Error;
+
+    fn try_from(table: wasmparser::Table<'a>) -> Result<Self> {
+        let table_type = table.ty;
+        let element_type = table_type.element_type;
+        if (element_type != wasmparser::RefType::FUNCREF && element_type != wasmparser::RefType::EXTERNREF) || table_type.table64 || table_type.shared {
+            return Err(anyhow::anyhow!("Unsupported table type {:?}", table_type));
+        }
+        let init = match table.init {
+            wasmparser::TableInit::RefNull => None,
+            wasmparser::TableInit::Expr(expr) => Some(expr.try_into()?),
+        };
+        Ok(Self { element_type: wasmparser::ValType::Ref(element_type).try_into()?, initial: table_type.initial as u32, maximum: table_type.maximum.map(|maximum| maximum as u32), init })
+    }
+}
+
+#[derive(Debug)]
+enum ElementKind {
+    Active { table: u32, offset: ConstExpr },
+    Passive,
+    Declared,
+}
+
+// Each element is a constant expression producing a reference, as it may
+// read a global. Plain function indices are turned into `ref.func`.
+#[derive(Debug)]
+struct ElementSegment {
+    kind: ElementKind,
+    element_type: ValType,
+    elements: Vec<ConstExpr>,
+}
+
+impl<'a> TryFrom<wasmparser::Element<'a>> for ElementSegment {
+    type Error = anyhow::Error;
+
+    fn try_from(element: wasmparser::Element<'a>) -> Result<Self> {
+        let (element_type, elements) = match element.items {
+            wasmparser::ElementItems::Functions(functions) => {
+                (ValType::FuncRef, functions.into_iter().map(|function| Ok(ConstExpr(vec![Instruction::RefFunc(function?)]))).collect::<Result<_>>()?)
+            },
+            wasmparser::ElementItems::Expressions(ref_type, expressions) => {
+                (wasmparser::ValType::Ref(ref_type).try_into()?, expressions.into_iter().map(|expression| expression?.try_into()).collect::<Result<_>>()?)
+            },
+        };
+        let kind = match element.kind {
+            wasmparser::ElementKind::Active { table_index, offset_expr } => {
+                ElementKind::Active { table: table_index.unwrap_or(0), offset: offset_expr.try_into()? }
+            },
+            wasmparser::ElementKind::Passive => ElementKind::Passive,
+            wasmparser::ElementKind::Declared => ElementKind::Declared,
+        };
+        Ok(Self { kind, element_type, elements })
+    }
+}
+
+// Exceptions. A catch clause's label is relative to the block enclosing the
+// `try_table`, and the `_ref` variants also pass on the caught `exnref`.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Catch {
+    One { tag: u32, label: u32 },
+    OneRef { tag: u32, label: u32 },
+    All { label: u32 },
+    AllRef { label: u32 },
+}
+
+impl From<wasmparser::Catch> for Catch {
+    fn from(catch: wasmparser::Catch) -> Self {
+        match catch {
+            wasmparser::Catch::One { tag, label } => Catch::One { tag, label },
+            wasmparser::Catch::OneRef { tag, label } => Catch::OneRef { tag, label },
+            wasmparser::Catch::All { label } => Catch::All { label },
+            wasmparser::Catch::AllRef { label } => Catch::AllRef { label },
+        }
+    }
+}
+
+#[derive(Debug)]
+struct TagType {
+    tag: Tag,
+    params: Vec<ValType>,
+}
+
+// Structured control flow is lowered to flat jumps. While a function body is
+// being compiled, every open `block`, `loop` and `if` is tracked here so that
+// its `end` (and `else`) can be patched into the instruction once it is known.
+#[derive(Debug)]
+struct ControlFrame {
+    start: usize,
+    else_: Option<usize>,
+}
+
 // Exports
 #[derive(Debug)]
 enum ExportKind {
@@ -186,8 +1314,16 @@ struct BytecodeBuilder {
     function_types: Vec<FuncType>,
     functions: Vec<Function>,
     exports: Exports,
-    first_function_index: Option<usize>,
+    globals: Vec<Global>,
+    tables: Vec<TableType>,
+    element_segments: Vec<ElementSegment>,
+    memories: Vec<MemoryType>,
+    data_segments: Vec<DataSegment>,
+    tags: Vec<TagType>,
+    start: Option<u32>,
+    imported_functions: usize,
     current_function_index: usize,
+    control_stack: Vec<ControlFrame>,
 }
 
 impl BytecodeBuilder {
@@ -196,8 +1332,16 @@ impl BytecodeBuilder {
             functions: Vec::new(),
             function_types: Vec::new(),
             exports: Exports::new(),
-            first_function_index: None,
+            globals: Vec::new(),
+            tables: Vec::new(),
+            element_segments: Vec::new(),
+            memories: Vec::new(),
+            data_segments: Vec::new(),
+            tags: Vec::new(),
+            start: None,
+            imported_functions: 0,
             current_function_index: 0,
+            control_stack: Vec::new(),
         }
     }
 
@@ -210,11 +1354,11 @@ impl BytecodeBuilder {
     }
 
     fn add_import(&mut self, func_type: FuncType, index: u32) {
+        self.imported_functions += 1;
         self.functions.push(Function::new(func_type, FunctKind::Import{index}));
     }
 
     fn add_function(&mut self, ty_index: usize) {
-        self.first_function_index = Some(self.functions.len());
         self.functions.push(Function::new(self.function_types[ty_index].clone(), FunctKind::Definition(FunctionDefinition::new())));
     }
 
@@ -222,14 +1366,143 @@ impl BytecodeBuilder {
         self.exports.add_export(name, export);
     }
 
+    fn add_global(&mut self, global: Global) {
+        self.globals.push(global);
+    }
+
+    fn add_table(&mut self, table: TableType) {
+        self.tables.push(table);
+    }
+
+    fn add_element_segment(&mut self, element_segment: ElementSegment) {
+        self.element_segments.push(element_segment);
+    }
+
+    fn add_memory(&mut self, memory: MemoryType) {
+        self.memories.push(memory);
+    }
+
+    fn add_data_segment(&mut self, data_segment: DataSegment) {
+        self.data_segments.push(data_segment);
+    }
+
+    fn add_tag(&mut self, tag: TagType) {
+        self.tags.push(tag);
+    }
+
+    fn set_start(&mut self, function_index: u32) {
+        self.start = Some(function_index);
+    }
+
+    // Imported functions take the first indices of the function index space, so
+    // the n-th code section entry is the body of function `imported_functions + n`.
+    fn current_function(&mut self) -> &mut Function {
+        let func_index = self.imported_functions + self.current_function_index;
+        &mut self.functions[func_index]
+    }
+
     fn add_local(&mut self, local: ValType) {
-        let func_index = self.current_function_index + self.first_function_index.unwrap_or(0);
-        self.functions[func_index].add_local(local);
+        self.current_function().add_local(local);
     }
 
     fn add_instruction(&mut self, instruction: Instruction) {
-        let func_index = self.current_function_index + self.first_function_index.unwrap_or(0);
-        self.functions[func_index].add_instruction(instruction);
+        self.current_function().add_instruction(instruction);
+    }
+
+    fn next_instruction_index(&mut self) -> u32 {
+        self.current_function().definition_mut().body.len() as u32
+    }
+
+    // Returns the number of parameters and results of a block.
+    fn block_type(&self, block_type: wasmparser::BlockType) -> Result<(u32, u32)> {
+        match block_type {
+            wasmparser::BlockType::Empty => Ok((0, 0)),
+            wasmparser::BlockType::Type(val_type) => {
+                ValType::try_from(val_type)?;
+                Ok((0, 1))
+            },
+            wasmparser::BlockType::FuncType(index) => {
+                let func_type = self.get_function_type(index as usize).ok_or(anyhow::anyhow!("Invalid function type index"))?;
+                Ok((func_type.params.len() as u32, func_type.returns.len() as u32))
+            },
+        }
+    }
+
+    fn add_operator(&mut self, operator: wasmparser::Operator) -> Result<()> {
+        match operator {
+            wasmparser::Operator::Block { blockty } => {
+                let (params, arity) = self.block_type(blockty)?;
+                let start = self.next_instruction_index() as usize;
+                self.control_stack.push(ControlFrame { start, else_: None });
+                self.add_instruction(Instruction::Block { params, arity, end: 0 });
+            },
+            wasmparser::Operator::TryTable { try_table } => {
+                let (params, arity) = self.block_type(try_table.ty)?;
+                let catches = try_table.catches.into_iter().map(Catch::from).collect();
+                let start = self.next_instruction_index() as usize;
+                self.control_stack.push(ControlFrame { start, else_: None });
+                self.add_instruction(Instruction::TryTable { params, arity, end: 0, catches });
+            },
+            wasmparser::Operator::Loop { blockty } => {
+                // Branching to a loop re-enters it, so the label carries the loop's
+                // parameters rather than its results.
+                let (arity, _) = self.block_type(blockty)?;
+                let start = self.next_instruction_index() as usize;
+                self.control_stack.push(ControlFrame { start, else_: None });
+                self.add_instruction(Instruction::Loop { arity });
+            },
+            wasmparser::Operator::If { blockty } => {
+                let (params, arity) = self.block_type(blockty)?;
+                let start = self.next_instruction_index() as usize;
+                self.control_stack.push(ControlFrame { start, else_: None });
+                self.add_instruction(Instruction::If { params, arity, else_: 0, end: 0 });
+            },
+            wasmparser::Operator::Else => {
+                let index = self.next_instruction_index();
+                let frame = self.control_stack.last_mut().ok_or(anyhow::anyhow!("Else outside of an if block"))?;
+                frame.else_ = Some(index as usize);
+                let start = frame.start;
+                match &mut self.current_function().definition_mut().body[start] {
+                    Instruction::If { else_, .. } => *else_ = index + 1,
+                    _ => return Err(anyhow::anyhow!("Else outside of an if block")),
+                }
+                self.add_instruction(Instruction::Else { end: 0 });
+            },
+            wasmparser::Operator::End => {
+                let index = self.next_instruction_index();
+                if let Some(frame) = self.control_stack.pop() {
+                    let body = &mut self.current_function().definition_mut().body;
+                    match &mut body[frame.start] {
+                        Instruction::Block { end, .. } | Instruction::TryTable { end, .. } => *end = index,
+                        Instruction::If { else_, end, .. } => {
+                            *end = index;
+                            if frame.else_.is_none() {
+                                *else_ = index;
+                            }
+                        },
+                        _ => {},
+                    }
+                    if let Some(Instruction::Else { end }) = frame.else_.map(|else_| &mut body[else_]) {
+                        *end = index;
+                    }
+                }
+                self.add_instruction(Instruction::End);
+            },
+            wasmparser::Operator::GlobalSet { global_index } => {
+                let global = self.globals.get(global_index as usize).ok_or(anyhow::anyhow!("Invalid global index"))?;
+                if !global.global_type.mutable {
+                    return Err(anyhow::anyhow!("Global {} is immutable", global_index));
+                }
+                self.add_instruction(Instruction::GlobalSet(global_index));
+            },
+            wasmparser::Operator::BrTable { targets } => {
+                let default = targets.default();
+                let targets = targets.targets().collect::<wasmparser::Result<Box<[u32]>>>()?;
+                self.add_instruction(Instruction::BrTable { targets, default });
+            },
+            operator => self.add_instruction(operator.try_into()?),
+        }
+        Ok(())
     }
 
     fn next_function(&mut self) {
@@ -237,9 +1510,19 @@ impl BytecodeBuilder {
     }
 
     fn build(self) -> Bytecode {
+        static NEXT_ID: AtomicU32 = AtomicU32::new(0);
         Bytecode {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            function_types: self.function_types,
             functions: self.functions,
             exports: self.exports,
+            globals: self.globals,
+            tables: self.tables,
+            element_segments: self.element_segments,
+            memories: self.memories,
+            data_segments: self.data_segments,
+            tags: self.tags,
+            start: self.start,
         }
     }
 }
@@ -255,15 +1538,103 @@ impl Import {
     }
 }
 
-#[derive(Debug)]
+// Values are kept as raw bits: integers as their two's complement value,
+// floats as their IEEE-754 bit pattern, so NaN payloads survive untouched, and
+// vectors as their little-endian bytes.
+#[derive(Debug, Clone)]
 pub struct Value {
     val_type: ValType,
-    value: i64,
+    value: i128,
 }
 
 impl Value {
     pub fn new(val_type: ValType, value: i64) -> Self {
-        Self { val_type, value }
+        Self { val_type, value: value as i128 }
+    }
+
+    pub fn i32(value: i32) -> Self {
+        Self::new(ValType::I32, value as i64)
+    }
+
+    pub fn i64(value: i64) -> Self {
+        Self::new(ValType::I64, value)
+    }
+
+    pub fn f32(value: f32) -> Self {
+        Self::new(ValType::F32, value.to_bits() as i64)
+    }
+
+    pub fn f64(value: f64) -> Self {
+        Self::new(ValType::F64, value.to_bits() as i64)
+    }
+
+    pub fn v128(value: u128) -> Self {
+        Self { val_type: ValType::V128, value: value as i128 }
+    }
+
+    // A `funcref` is null or the index of a function.
+    pub fn func_ref(index: Option<u32>) -> Self {
+        Self::new(ValType::FuncRef, index.map_or(-1, |index| index as i64))
+    }
+
+    // Non-null `externref`s only come from `Vm::new_extern`, since their
+    // handles index the objects the Vm owns.
+    pub fn null_extern_ref() -> Self {
+        Self::extern_ref(None)
+    }
+
+    pub(crate) fn extern_ref(handle: Option<u32>) -> Self {
+        Self::new(ValType::ExternRef, handle.map_or(-1, |handle| handle as i64))
+    }
+
+    // The value a local of the given type starts with.
+    pub(crate) fn default_for(val_type: ValType) -> Self {
+        match val_type {
+            ValType::FuncRef | ValType::ExternRef | ValType::ExnRef => Self::new(val_type, -1),
+            _ => Self::new(val_type, 0),
+        }
+    }
+
+    pub fn as_i32(&self) -> Option<i32> {
+        (self.val_type == ValType::I32).then_some(self.value as i32)
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        (self.val_type == ValType::I64).then_some(self.value as i64)
+    }
+
+    pub fn as_f32(&self) -> Option<f32> {
+        (self.val_type == ValType::F32).then_some(f32::from_bits(self.value as u32))
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        (self.val_type == ValType::F64).then_some(f64::from_bits(self.value as u64))
+    }
+
+    pub fn as_v128(&self) -> Option<u128> {
+        (self.val_type == ValType::V128).then_some(self.value as u128)
+    }
+
+    pub fn as_func_ref(&self) -> Option<Option<u32>> {
+        (self.val_type == ValType::FuncRef).then_some(u32::try_from(self.value).ok())
+    }
+
+    pub fn as_extern_ref(&self) -> Option<Option<u32>> {
+        (self.val_type == ValType::ExternRef).then_some(u32::try_from(self.value).ok())
+    }
+
+    pub fn as_exn_ref(&self) -> Option<Option<u32>> {
+        (self.val_type == ValType::ExnRef).then_some(u32::try_from(self.value).ok())
+    }
+
+    pub fn val_type(&self) -> ValType {
+        self.val_type
+    }
+
+    // The raw bits of a scalar or reference. Vectors only keep their low 64
+    // bits here; use `as_v128` for all of them.
+    pub fn value(&self) -> i64 {
+        self.value as i64
     }
 }
 
@@ -274,21 +1645,70 @@ pub enum Return {
     Multiple(Vec<Value>),
 }
 
+pub type ImportFn = Box<dyn FnMut(Vec<Value>) -> Result<Return>>;
+
+struct GlobalImport {
+    global_type: GlobalType,
+    value: Value,
+}
+
 pub struct Imports {
     imports: HashMap<(&'static str, &'static str), Import>,
-    import_fns: Vec<Box<dyn FnMut(Vec<Value>) -> Result<Return>>>,
+    import_fns: Vec<ImportFn>,
+    globals: HashMap<(&'static str, &'static str), GlobalImport>,
+    tags: HashMap<(&'static str, &'static str), TagType>,
+}
+
+impl Default for Imports {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Imports {
     pub fn new() -> Self {
-        Self { imports: HashMap::new(), import_fns: Vec::new() }
+        Self { imports: HashMap::new(), import_fns: Vec::new(), globals: HashMap::new(), tags: HashMap::new() }
     }
     
-    pub fn add_import(&mut self, module: &'static str, name: &'static str, params: Vec<ValType>, returns: Vec<ValType>, import_fn: Box<dyn FnMut(Vec<Value>) -> Result<Return>>) {
+    pub fn add_import(&mut self, module: &'static str, name: &'static str, params: Vec<ValType>, returns: Vec<ValType>, import_fn: ImportFn) {
         self.imports.insert((module, name), Import::new(FuncType::new(params, returns), self.import_fns.len() as u32));
         self.import_fns.push(import_fn);
     }
 
+    // Defines a global for modules to import. A mutable one is shared with
+    // every instance importing it: they read it when a call starts and write
+    // it back when the call returns.
+    pub fn add_global(&mut self, module: &'static str, name: &'static str, value: Value, mutable: bool) {
+        let global_type = GlobalType { val_type: value.val_type, mutable };
+        self.globals.insert((module, name), GlobalImport { global_type, value });
+    }
+
+    // The current value of a global defined with `add_global`.
+    pub fn global(&self, module: &str, name: &str) -> Option<Value> {
+        self.get_global(module, name).map(|global| global.value.clone())
+    }
+
+    // Defines a tag for the module to import. The returned tag is what the host
+    // throws and matches caught exceptions against.
+    pub fn add_tag(&mut self, module: &'static str, name: &'static str, params: Vec<ValType>) -> Tag {
+        let tag = Tag::new();
+        self.tags.insert((module, name), TagType { tag, params });
+        tag
+    }
+
+    fn get_tag<'a>(&'a self, module: &'a str, name: &'a str) -> Option<&'a TagType> {
+        self.tags.get(&(module, name))
+    }
+
+    fn get_global<'a>(&'a self, module: &'a str, name: &'a str) -> Option<&'a GlobalImport> {
+        self.globals.get(&(module, name))
+    }
+
+    // A mutable lookup can't shorten the `'static` keys, so it searches.
+    fn get_global_mut(&mut self, module: &str, name: &str) -> Option<&mut GlobalImport> {
+        self.globals.iter_mut().find(|(key, _)| **key == (module, name)).map(|(_, global)| global)
+    }
+
     fn get_import<'a>(&'a self, module: &'a str, name: &'a str) -> Option<&'a Import> {
         self.imports.get(&(module, name))
     }
@@ -317,24 +1737,74 @@ impl Exports {
     }
 }
 
+// A compiled module. Every one gets its own `id`, which lets a `Vm` tell
+// whether it was instantiated from it.
 #[derive(Debug)]
 pub struct Bytecode {
+    id: u32,
+    function_types: Vec<FuncType>,
     functions: Vec<Function>,
     exports: Exports,
+    globals: Vec<Global>,
+    tables: Vec<TableType>,
+    element_segments: Vec<ElementSegment>,
+    memories: Vec<MemoryType>,
+    data_segments: Vec<DataSegment>,
+    tags: Vec<TagType>,
+    start: Option<u32>,
 }
 
 impl Bytecode {
+    pub(crate) fn id(&self) -> u32 {
+        self.id
+    }
+
     pub(crate) fn get_function(&self, name: &str) -> Option<&Function> {
-        self.exports.get_export(name).and_then(|export| self.functions.get(export.index as usize))
+        self.exports.get_export(name)
+            .filter(|export| matches!(export.kind, ExportKind::Function))
+            .and_then(|export| self.functions.get(export.index as usize))
     }
 
     pub(crate) fn get_function_by_index(&self, index: usize) -> Option<&Function> {
         self.functions.get(index)
     }
+
+    pub(crate) fn get_function_type(&self, index: usize) -> Option<&FuncType> {
+        self.function_types.get(index)
+    }
+
+    pub fn get_tag(&self, name: &str) -> Option<Tag> {
+        self.exports.get_export(name)
+            .filter(|export| matches!(export.kind, ExportKind::Tag))
+            .and_then(|export| self.tags.get(export.index as usize))
+            .map(|tag| tag.tag)
+    }
 }
 
+// The proposals the interpreter implements. Anything else, such as GC or
+// typed function references, fails validation instead of being lowered
+// half-way. `GC_TYPES` is what lets reference types appear at all; `GC`
+// itself stays off.
+const SUPPORTED_FEATURES: WasmFeatures = WasmFeatures::FLOATS
+    .union(WasmFeatures::GC_TYPES)
+    .union(WasmFeatures::MUTABLE_GLOBAL)
+    .union(WasmFeatures::SATURATING_FLOAT_TO_INT)
+    .union(WasmFeatures::SIGN_EXTENSION)
+    .union(WasmFeatures::MULTI_VALUE)
+    .union(WasmFeatures::BULK_MEMORY)
+    .union(WasmFeatures::MULTI_MEMORY)
+    .union(WasmFeatures::REFERENCE_TYPES)
+    .union(WasmFeatures::SIMD)
+    .union(WasmFeatures::RELAXED_SIMD)
+    .union(WasmFeatures::TAIL_CALL)
+    .union(WasmFeatures::EXCEPTIONS)
+    .union(WasmFeatures::EXTENDED_CONST);
+
 // Main compilation function
 pub fn compile_wasm(wasm: &[u8], imports: &Imports) -> Result<Bytecode> {
+    // Only valid modules are lowered, so the interpreter can rely on operand
+    // types, branch depths and indices being consistent.
+    wasmparser::Validator::new_with_features(SUPPORTED_FEATURES).validate_all(wasm)?;
     let parser = Parser::new(0);
     let mut bytecode_builder = BytecodeBuilder::new();
     for payload in parser.parse_all(wasm) {
@@ -344,9 +1814,9 @@ pub fn compile_wasm(wasm: &[u8], imports: &Imports) -> Result<Bytecode> {
                 println!("Version: {:?}, encoding: {:?}, range: {:?}", num, encoding, range);
             },
             wasmparser::Payload::TypeSection(section_limited) => {
-                for (i, ty) in section_limited.into_iter_err_on_gc_types().enumerate() {
+                for ty in section_limited.into_iter_err_on_gc_types() {
                     let func_type = ty?;
-                    bytecode_builder.add_function_type(func_type.into());
+                    bytecode_builder.add_function_type(func_type.try_into()?);
                 }
             },
             wasmparser::Payload::ImportSection(section_limited) => {
@@ -361,8 +1831,24 @@ pub fn compile_wasm(wasm: &[u8], imports: &Imports) -> Result<Bytecode> {
                             }
                             bytecode_builder.add_import(import.func_type.clone(), import.index);
                         },
-                        _ => todo!(),
-                    } 
+                        wasmparser::TypeRef::Global(global_type) => {
+                            let global = imports.get_global(import.module, import.name).ok_or(anyhow::anyhow!("Global import not found in HashMap"))?;
+                            if global.global_type != global_type.try_into()? {
+                                return Err(anyhow::anyhow!("Import global type does not match declared global type"));
+                            }
+                            let init = GlobalInit::Import { module: import.module.to_string(), name: import.name.to_string() };
+                            bytecode_builder.add_global(Global { global_type: global.global_type, init });
+                        },
+                        wasmparser::TypeRef::Tag(tag_type) => {
+                            let func_type = bytecode_builder.get_function_type(tag_type.func_type_idx as usize).ok_or(anyhow::anyhow!("Invalid function type index"))?;
+                            let tag = imports.get_tag(import.module, import.name).ok_or(anyhow::anyhow!("Tag import not found in HashMap"))?;
+                            if func_type.params != tag.params {
+                                return Err(anyhow::anyhow!("Import tag type does not match declared tag type"));
+                            }
+                            bytecode_builder.add_tag(TagType { tag: tag.tag, params: tag.params.clone() });
+                        },
+                        _ => return Err(anyhow::anyhow!("Import {}::{} of kind {:?} is not supported", import.module, import.name, import.ty)),
+                    }
                 }
             },
             wasmparser::Payload::FunctionSection(section_limited) => {
@@ -372,16 +1858,28 @@ pub fn compile_wasm(wasm: &[u8], imports: &Imports) -> Result<Bytecode> {
                 }
             },
             wasmparser::Payload::TableSection(section_limited) => {
-                println!("Table Section: {:?}", section_limited);
+                for table in section_limited.into_iter() {
+                    bytecode_builder.add_table(table?.try_into()?);
+                }
             },
             wasmparser::Payload::MemorySection(section_limited) => {
-                println!("Memory Section: {:?}", section_limited);
+                for memory in section_limited.into_iter() {
+                    bytecode_builder.add_memory(memory?.try_into()?);
+                }
             },
             wasmparser::Payload::TagSection(section_limited) => {
-                println!("Tag Section: {:?}", section_limited);
+                for tag_type in section_limited.into_iter() {
+                    let tag_type = tag_type?;
+                    let func_type = bytecode_builder.get_function_type(tag_type.func_type_idx as usize).ok_or(anyhow::anyhow!("Invalid function type index"))?;
+                    let params = func_type.params.clone();
+                    bytecode_builder.add_tag(TagType { tag: Tag::new(), params });
+                }
             },
             wasmparser::Payload::GlobalSection(section_limited) => {
-                println!("Global Section: {:?}", section_limited);
+                for global in section_limited.into_iter() {
+                    let global = global?;
+                    bytecode_builder.add_global(Global { global_type: global.ty.try_into()?, init: GlobalInit::ConstExpr(global.init_expr.try_into()?) });
+                }
             },
             wasmparser::Payload::ExportSection(section_limited) => {
                 for export in section_limited.into_iter() {
@@ -389,31 +1887,35 @@ pub fn compile_wasm(wasm: &[u8], imports: &Imports) -> Result<Bytecode> {
                     bytecode_builder.add_export(export.name.to_string(), export.into());
                 }
             },
-            wasmparser::Payload::StartSection { func, range } => {
-                println!("Start Section: func: {:?}, range: {:?}", func, range);
+            wasmparser::Payload::StartSection { func, .. } => {
+                bytecode_builder.set_start(func);
             },
             wasmparser::Payload::ElementSection(section_limited) => {
-                println!("Element Section: {:?}", section_limited);
-            },
-            wasmparser::Payload::DataCountSection { count, range } => {
-                println!("Data Count Section: count: {:?}, range: {:?}", count, range);
+                for element in section_limited.into_iter() {
+                    bytecode_builder.add_element_segment(element?.try_into()?);
+                }
             },
+            // The data count only lets the validator check `memory.init` and
+            // `data.drop` ahead of the data section.
+            wasmparser::Payload::DataCountSection { .. } => {},
             wasmparser::Payload::DataSection(section_limited) => {
-                println!("Data Section: {:?}", section_limited);
+                for data in section_limited.into_iter() {
+                    bytecode_builder.add_data_segment(data?.try_into()?);
+                }
             },
             wasmparser::Payload::CodeSectionStart { count: _, range: _, size: _ } => {},
             wasmparser::Payload::CodeSectionEntry(function_body) => {
                 let locals_reader = function_body.get_locals_reader()?;
                 for local in locals_reader.into_iter() {
                     let (n, local) = local?;
+                    let local = local.try_into()?;
                     for _ in 0..n {
-                        bytecode_builder.add_local(local.into());
+                        bytecode_builder.add_local(local);
                     }
                 }
                 let operators_reader = function_body.get_operators_reader()?;
                 for operator in operators_reader.into_iter() {
-                    let operator = operator?;
-                    bytecode_builder.add_instruction(operator.into());
+                    bytecode_builder.add_operator(operator?)?;
                 }
                 bytecode_builder.next_function();
             },
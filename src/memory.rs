@@ -0,0 +1,76 @@
+use anyhow::Result;
+
+use crate::trap::{Trap, TrapKind};
+
+pub(crate) const PAGE_SIZE: usize = 65536;
+// The most pages a 32-bit memory can address.
+pub(crate) const MAX_PAGES: u32 = 65536;
+
+// Linear memory
+#[derive(Debug)]
+pub(crate) struct Memory {
+    data: Vec<u8>,
+    maximum: u32,
+}
+
+impl Memory {
+    pub(crate) fn new(initial: u32, maximum: u32) -> Self {
+        Self { data: vec![0; initial as usize * PAGE_SIZE], maximum }
+    }
+
+    pub(crate) fn size(&self) -> u32 {
+        (self.data.len() / PAGE_SIZE) as u32
+    }
+
+    // Returns the previous size in pages, or `None` if the memory cannot grow
+    // by `delta` pages.
+    pub(crate) fn grow(&mut self, delta: u32) -> Option<u32> {
+        let size = self.size();
+        let new_size = size.checked_add(delta).filter(|new_size| *new_size <= self.maximum)?;
+        let additional = delta as usize * PAGE_SIZE;
+        self.data.try_reserve_exact(additional).ok()?;
+        self.data.resize(new_size as usize * PAGE_SIZE, 0);
+        Some(size)
+    }
+
+    fn range(&self, address: u64, len: usize) -> Result<std::ops::Range<usize>> {
+        let end = address.checked_add(len as u64).filter(|end| *end <= self.data.len() as u64);
+        match end {
+            Some(end) => Ok(address as usize..end as usize),
+            None => Err(Trap::new(TrapKind::MemoryOutOfBounds).into()),
+        }
+    }
+
+    pub(crate) fn read<const N: usize>(&self, address: u64) -> Result<[u8; N]> {
+        let range = self.range(address, N)?;
+        let mut bytes = [0; N];
+        bytes.copy_from_slice(&self.data[range]);
+        Ok(bytes)
+    }
+
+    pub(crate) fn write(&mut self, address: u64, bytes: &[u8]) -> Result<()> {
+        let range = self.range(address, bytes.len())?;
+        self.data[range].copy_from_slice(bytes);
+        Ok(())
+    }
+
+    pub(crate) fn slice(&self, address: u64, len: usize) -> Result<&[u8]> {
+        let range = self.range(address, len)?;
+        Ok(&self.data[range])
+    }
+
+    pub(crate) fn fill(&mut self, address: u64, value: u8, len: usize) -> Result<()> {
+        let range = self.range(address, len)?;
+        self.data[range].fill(value);
+        Ok(())
+    }
+
+    // Both ranges are checked before anything is written, and `copy_within`
+    // handles overlapping ranges like `memmove`.
+    pub(crate) fn copy_within(&mut self, destination: u64, source: u64, len: usize) -> Result<()> {
+        let source = self.range(source, len)?;
+        let destination = self.range(destination, len)?;
+        self.data.copy_within(source, destination.start);
+        Ok(())
+    }
+}
@@ -0,0 +1,117 @@
+// Lane access for `v128` values. A vector is kept as a `u128` holding its
+// little-endian memory layout, so lane 0 sits in the lowest bits.
+
+pub(crate) trait Lane: Copy {
+    const BITS: u32;
+    // Takes the lane from the low bits of `bits`.
+    fn from_bits(bits: u128) -> Self;
+    // The lane's bit pattern, zero-extended.
+    fn to_bits(self) -> u128;
+}
+
+macro_rules! int_lane {
+    ($($int:ty => $unsigned:ty),*) => {
+        $(
+            impl Lane for $int {
+                const BITS: u32 = <$int>::BITS;
+
+                fn from_bits(bits: u128) -> Self {
+                    bits as $int
+                }
+
+                fn to_bits(self) -> u128 {
+                    self as $unsigned as u128
+                }
+            }
+        )*
+    };
+}
+
+int_lane!(i8 => u8, u8 => u8, i16 => u16, u16 => u16, i32 => u32, u32 => u32, i64 => u64, u64 => u64);
+
+impl Lane for f32 {
+    const BITS: u32 = 32;
+
+    fn from_bits(bits: u128) -> Self {
+        f32::from_bits(bits as u32)
+    }
+
+    fn to_bits(self) -> u128 {
+        self.to_bits() as u128
+    }
+}
+
+impl Lane for f64 {
+    const BITS: u32 = 64;
+
+    fn from_bits(bits: u128) -> Self {
+        f64::from_bits(bits as u64)
+    }
+
+    fn to_bits(self) -> u128 {
+        self.to_bits() as u128
+    }
+}
+
+fn lane_mask<T: Lane>() -> u128 {
+    u128::MAX >> (128 - T::BITS)
+}
+
+pub(crate) fn lane<T: Lane>(vector: u128, index: usize) -> T {
+    T::from_bits(vector >> (index * T::BITS as usize))
+}
+
+pub(crate) fn replace_lane<T: Lane>(vector: u128, index: usize, value: T) -> u128 {
+    let shift = index * T::BITS as usize;
+    vector & !(lane_mask::<T>() << shift) | value.to_bits() << shift
+}
+
+pub(crate) fn lanes<T: Lane>(vector: u128) -> impl Iterator<Item = T> {
+    (0..128 / T::BITS as usize).map(move |index| lane(vector, index))
+}
+
+// Packs lanes into a vector, starting from lane 0. Missing lanes are zero.
+pub(crate) fn from_lanes<T: Lane>(lanes: impl IntoIterator<Item = T>) -> u128 {
+    lanes.into_iter().enumerate().fold(0, |vector, (index, lane)| vector | lane.to_bits() << (index * T::BITS as usize))
+}
+
+pub(crate) fn splat<T: Lane>(value: T) -> u128 {
+    from_lanes(std::iter::repeat_n(value, 128 / T::BITS as usize))
+}
+
+// Comparisons set every bit of a lane that satisfies them.
+pub(crate) fn compare<T: Lane>(a: u128, b: u128, op: impl Fn(T, T) -> bool) -> u128 {
+    lanes::<T>(a).zip(lanes::<T>(b)).enumerate()
+        .filter(|(_, (a, b))| op(*a, *b))
+        .fold(0, |vector, (index, _)| vector | lane_mask::<T>() << (index * T::BITS as usize))
+}
+
+// Gathers the top bit of every lane, lane 0 first.
+pub(crate) fn bitmask<T: Lane>(vector: u128) -> i32 {
+    lanes::<T>(vector).enumerate().fold(0, |mask, (index, lane)| mask | ((lane.to_bits() >> (T::BITS - 1)) as i32) << index)
+}
+
+pub(crate) fn all_true<T: Lane>(vector: u128) -> i32 {
+    lanes::<T>(vector).all(|lane| lane.to_bits() != 0) as i32
+}
+
+// Picks bytes of `a` and then `b` by index, with any index past both as zero.
+pub(crate) fn shuffle(a: u128, b: u128, indices: impl Iterator<Item = u8>) -> u128 {
+    let bytes: Vec<u8> = a.to_le_bytes().into_iter().chain(b.to_le_bytes()).collect();
+    from_lanes(indices.map(|index| bytes.get(index as usize).copied().unwrap_or(0)))
+}
+
+// Saturating rounding Q15 multiplication, as done by `i16x8.q15mulr_sat_s`.
+pub(crate) fn q15mulr_sat(a: i16, b: i16) -> i16 {
+    ((a as i32 * b as i32 + 0x4000) >> 15).clamp(i16::MIN as i32, i16::MAX as i32) as i16
+}
+
+// Adjacent lanes, two at a time, for the pairwise operations.
+pub(crate) fn pairs<T: Lane>(vector: u128) -> impl Iterator<Item = (T, T)> {
+    (0..64 / T::BITS as usize).map(move |index| (lane(vector, 2 * index), lane(vector, 2 * index + 1)))
+}
+
+// The signed 8-bit dot product into 16-bit lanes of the relaxed dot products.
+pub(crate) fn relaxed_dot(a: u128, b: u128) -> u128 {
+    from_lanes(pairs::<i8>(a).zip(pairs::<i8>(b)).map(|((a1, a2), (b1, b2))| (a1 as i16 * b1 as i16).wrapping_add(a2 as i16 * b2 as i16)))
+}
@@ -0,0 +1,85 @@
+use anyhow::Result;
+
+use crate::trap::{Trap, TrapKind};
+use crate::ValType;
+
+// Tables of references. An element is either null or the index of the
+// function, or host handle, it refers to.
+#[derive(Debug)]
+pub(crate) struct Table {
+    element_type: ValType,
+    elements: Vec<Option<u32>>,
+    maximum: u32,
+}
+
+impl Table {
+    pub(crate) fn new(element_type: ValType, initial: u32, maximum: u32, init: Option<u32>) -> Self {
+        Self { element_type, elements: vec![init; initial as usize], maximum }
+    }
+
+    pub(crate) fn element_type(&self) -> ValType {
+        self.element_type
+    }
+
+    pub(crate) fn elements(&self) -> &[Option<u32>] {
+        &self.elements
+    }
+
+    pub(crate) fn size(&self) -> u32 {
+        self.elements.len() as u32
+    }
+
+    // Returns the previous size, or `None` if the table cannot grow by `delta`
+    // elements.
+    pub(crate) fn grow(&mut self, delta: u32, init: Option<u32>) -> Option<u32> {
+        let size = self.size();
+        let new_size = size.checked_add(delta).filter(|new_size| *new_size <= self.maximum)?;
+        self.elements.try_reserve_exact(delta as usize).ok()?;
+        self.elements.resize(new_size as usize, init);
+        Some(size)
+    }
+
+    fn range(&self, index: u32, len: u32) -> Result<std::ops::Range<usize>> {
+        let end = index.checked_add(len).filter(|end| *end as usize <= self.elements.len());
+        match end {
+            Some(end) => Ok(index as usize..end as usize),
+            None => Err(Trap::new(TrapKind::TableOutOfBounds).into()),
+        }
+    }
+
+    pub(crate) fn get(&self, index: u32) -> Result<Option<u32>> {
+        let element = self.elements.get(index as usize).ok_or(Trap::new(TrapKind::TableOutOfBounds))?;
+        Ok(*element)
+    }
+
+    pub(crate) fn set(&mut self, index: u32, value: Option<u32>) -> Result<()> {
+        let element = self.elements.get_mut(index as usize).ok_or(Trap::new(TrapKind::TableOutOfBounds))?;
+        *element = value;
+        Ok(())
+    }
+
+    pub(crate) fn slice(&self, index: u32, len: u32) -> Result<&[Option<u32>]> {
+        let range = self.range(index, len)?;
+        Ok(&self.elements[range])
+    }
+
+    pub(crate) fn init(&mut self, offset: u32, elements: &[Option<u32>]) -> Result<()> {
+        let len = u32::try_from(elements.len()).map_err(|_| Trap::new(TrapKind::TableOutOfBounds))?;
+        let range = self.range(offset, len)?;
+        self.elements[range].copy_from_slice(elements);
+        Ok(())
+    }
+
+    pub(crate) fn fill(&mut self, index: u32, value: Option<u32>, len: u32) -> Result<()> {
+        let range = self.range(index, len)?;
+        self.elements[range].fill(value);
+        Ok(())
+    }
+
+    pub(crate) fn copy_within(&mut self, destination: u32, source: u32, len: u32) -> Result<()> {
+        let source = self.range(source, len)?;
+        let destination = self.range(destination, len)?;
+        self.elements.copy_within(source, destination.start);
+        Ok(())
+    }
+}
@@ -0,0 +1,56 @@
+use std::fmt;
+
+// Traps abort execution of the guest. They are returned as errors from the
+// `Vm` and can be told apart from other failures by downcasting to `Trap`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrapKind {
+    Unreachable,
+    IntegerOverflow,
+    IntegerDivideByZero,
+    InvalidConversionToInteger,
+    MemoryOutOfBounds,
+    TableOutOfBounds,
+    IndirectCallToNull,
+    IndirectCallTypeMismatch,
+    NullReference,
+    DanglingReference,
+    StackExhausted,
+    StackUnderflow,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trap {
+    kind: TrapKind,
+}
+
+impl Trap {
+    pub fn new(kind: TrapKind) -> Self {
+        Self { kind }
+    }
+
+    pub fn kind(&self) -> TrapKind {
+        self.kind
+    }
+}
+
+impl fmt::Display for Trap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self.kind {
+            TrapKind::Unreachable => "unreachable executed",
+            TrapKind::IntegerOverflow => "integer overflow",
+            TrapKind::IntegerDivideByZero => "integer divide by zero",
+            TrapKind::InvalidConversionToInteger => "invalid conversion to integer",
+            TrapKind::MemoryOutOfBounds => "out of bounds memory access",
+            TrapKind::TableOutOfBounds => "out of bounds table access",
+            TrapKind::IndirectCallToNull => "uninitialized element",
+            TrapKind::IndirectCallTypeMismatch => "indirect call type mismatch",
+            TrapKind::NullReference => "null reference",
+            TrapKind::DanglingReference => "reference to a released object",
+            TrapKind::StackExhausted => "call stack exhausted",
+            TrapKind::StackUnderflow => "operand stack underflow",
+        };
+        write!(f, "wasm trap: {}", message)
+    }
+}
+
+impl std::error::Error for Trap {}
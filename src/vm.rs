@@ -1,127 +1,1628 @@
-use crate::{Bytecode, Function, Imports, Instruction, Return, Value};
-use anyhow::Result;
+use std::any::Any;
+use std::collections::HashSet;
+
+use crate::exception::Exception;
+use crate::float::{canonicalize_f32, canonicalize_f64, checked_trunc, f32_max, f32_min, f64_max, f64_min};
+use crate::handles::Handles;
+use crate::memory::{Memory, MAX_PAGES};
+use crate::simd::{self, all_true, bitmask, from_lanes, lanes, pairs, q15mulr_sat, relaxed_dot, replace_lane, shuffle, splat, Lane};
+use crate::table::Table;
+use crate::trap::{Trap, TrapKind};
+use crate::{Bytecode, Catch, ConstExpr, DataKind, ElementKind, Function, GlobalInit, Imports, Instruction, MemArg, Return, ValType, Value};
+use anyhow::{Context, Result};
+
+// Moves values between operand stack slots and their Rust types. Integers are
+// kept sign-extended and floats as their zero-extended bit pattern. Slots are
+// wide enough for a whole `v128`.
+trait StackValue {
+    fn from_slot(slot: i128) -> Self;
+    fn into_slot(self) -> i128;
+}
+
+impl StackValue for i32 {
+    fn from_slot(slot: i128) -> Self {
+        slot as i32
+    }
+
+    fn into_slot(self) -> i128 {
+        self as i128
+    }
+}
+
+impl StackValue for u32 {
+    fn from_slot(slot: i128) -> Self {
+        slot as u32
+    }
+
+    fn into_slot(self) -> i128 {
+        self as i32 as i128
+    }
+}
+
+impl StackValue for i64 {
+    fn from_slot(slot: i128) -> Self {
+        slot as i64
+    }
+
+    fn into_slot(self) -> i128 {
+        self as i128
+    }
+}
+
+impl StackValue for u64 {
+    fn from_slot(slot: i128) -> Self {
+        slot as u64
+    }
+
+    fn into_slot(self) -> i128 {
+        self as i64 as i128
+    }
+}
+
+// References to functions are stored as their index, with null as -1.
+impl StackValue for Option<u32> {
+    fn from_slot(slot: i128) -> Self {
+        u32::try_from(slot).ok()
+    }
+
+    fn into_slot(self) -> i128 {
+        self.map_or(-1, |index| index as i128)
+    }
+}
+
+// Vectors, and raw slots whatever their type.
+impl StackValue for u128 {
+    fn from_slot(slot: i128) -> Self {
+        slot as u128
+    }
+
+    fn into_slot(self) -> i128 {
+        self as i128
+    }
+}
+
+impl StackValue for i128 {
+    fn from_slot(slot: i128) -> Self {
+        slot
+    }
+
+    fn into_slot(self) -> i128 {
+        self
+    }
+}
+
+impl StackValue for f32 {
+    fn from_slot(slot: i128) -> Self {
+        f32::from_bits(slot as u32)
+    }
+
+    fn into_slot(self) -> i128 {
+        self.to_bits() as i128
+    }
+}
+
+impl StackValue for f64 {
+    fn from_slot(slot: i128) -> Self {
+        f64::from_bits(slot as u64)
+    }
+
+    fn into_slot(self) -> i128 {
+        self.to_bits() as i128
+    }
+}
+
+const DEFAULT_MAX_CALL_DEPTH: usize = 100_000;
+
+pub struct Config {
+    max_memory_pages: u32,
+    max_call_depth: usize,
+    relaxed_simd_deterministic: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Config {
+    pub fn new() -> Self {
+        Self { max_memory_pages: MAX_PAGES, max_call_depth: DEFAULT_MAX_CALL_DEPTH, relaxed_simd_deterministic: false }
+    }
+
+    // Caps how many 64KiB pages any memory may grow to, on top of the maximum
+    // the module declares.
+    pub fn max_memory_pages(mut self, pages: u32) -> Self {
+        self.max_memory_pages = pages.min(MAX_PAGES);
+        self
+    }
+
+    // Caps how many wasm function calls may be active at once. Going deeper
+    // traps with `TrapKind::StackExhausted`.
+    pub fn max_call_depth(mut self, depth: usize) -> Self {
+        self.max_call_depth = depth.max(1);
+        self
+    }
+
+    // Relaxed SIMD operations may give different results on different engines
+    // and hosts. By default each one takes the cheapest behavior the proposal
+    // allows, which may change between versions. This pins them all to the
+    // deterministic behavior the proposal specifies instead.
+    pub fn relaxed_simd_deterministic(mut self, enabled: bool) -> Self {
+        self.relaxed_simd_deterministic = enabled;
+        self
+    }
+}
 
 pub struct Vm {
-    stack: Vec<i64>,
+    config: Config,
+    // The `Bytecode` the instance was created from, once instantiation succeeded.
+    module: Option<u32>,
+    stack: Vec<i128>,
+    globals: Vec<i128>,
+    global_types: Vec<ValType>,
+    // Mutable globals imported from the host, by index and import name.
+    shared_globals: Vec<(usize, String, String)>,
+    tables: Vec<Table>,
+    memories: Vec<Memory>,
+    dropped_data: Vec<bool>,
+    element_segments: Vec<Vec<Option<u32>>>,
+    element_types: Vec<ValType>,
+    externs: Handles<Box<dyn Any>>,
+    exceptions: Handles<Exception>,
+    // How many caught exceptions may pile up during a call before the
+    // unreachable ones are collected.
+    exception_limit: usize,
+}
+
+const MIN_EXCEPTION_LIMIT: usize = 1024;
+
+// A branch target. `height` is the operand stack height when the label was
+// entered, `arity` the number of values a branch carries to it and `target`
+// the instruction a branch continues at. Labels of a `try_table` also point
+// at it, for its catch clauses.
+#[derive(Debug, Clone, Copy)]
+struct Label {
+    arity: usize,
+    height: usize,
+    target: usize,
+    try_table: Option<usize>,
+}
+
+// A function activation. The function returns once its last label, the one of
+// its body, is gone.
+struct Frame<'a> {
+    body: &'a [Instruction],
+    pc: usize,
+    locals: Vec<Value>,
+    labels: Vec<Label>,
+}
+
+// What the run loop does after an instruction: calls are performed there, so
+// that guest recursion grows the frame stack rather than the native one.
+enum Control {
+    Continue,
+    Call(u32),
+    TailCall(u32),
+}
+
+impl Default for Vm {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Vm {
     pub fn new() -> Self {
-        Vm { stack: Vec::new() }
+        Self::with_config(Config::new())
+    }
+
+    pub fn with_config(config: Config) -> Self {
+        Vm {
+            config,
+            module: None,
+            stack: Vec::new(),
+            globals: Vec::new(),
+            global_types: Vec::new(),
+            shared_globals: Vec::new(),
+            tables: Vec::new(),
+            memories: Vec::new(),
+            dropped_data: Vec::new(),
+            element_segments: Vec::new(),
+            element_types: Vec::new(),
+            externs: Handles::default(),
+            exceptions: Handles::default(),
+            exception_limit: MIN_EXCEPTION_LIMIT,
+        }
+    }
+
+    pub fn instantiate(&mut self, bytecode: &Bytecode, imports: &mut Imports) -> Result<()> {
+        self.module = None;
+        self.globals = Vec::new();
+        self.shared_globals = Vec::new();
+        for global in bytecode.globals.iter() {
+            let value = match &global.init {
+                GlobalInit::Import { module, name } => {
+                    let import = imports.get_global(module, name).ok_or_else(|| anyhow::anyhow!("Global import {}::{} not found", module, name))?;
+                    if import.global_type != global.global_type {
+                        return Err(anyhow::anyhow!("Import global type does not match declared global type"));
+                    }
+                    if global.global_type.mutable {
+                        self.shared_globals.push((self.globals.len(), module.clone(), name.clone()));
+                    }
+                    import.value.value
+                },
+                GlobalInit::ConstExpr(const_expr) => self.eval_const_expr(const_expr)?,
+            };
+            self.globals.push(value);
+        }
+        self.global_types = bytecode.globals.iter().map(|global| global.global_type.val_type).collect();
+        let mut tables = Vec::new();
+        for table in bytecode.tables.iter() {
+            let init = match &table.init {
+                Some(init) => Option::<u32>::from_slot(self.eval_const_expr(init)?),
+                None => None,
+            };
+            tables.push(Table::new(table.element_type, table.initial, table.maximum.unwrap_or(u32::MAX), init));
+        }
+        self.tables = tables;
+        let mut memories = Vec::new();
+        for memory in bytecode.memories.iter() {
+            let maximum = memory.maximum.unwrap_or(MAX_PAGES).min(self.config.max_memory_pages);
+            if memory.initial > maximum {
+                return Err(anyhow::anyhow!("Memory of {} pages exceeds the limit of {} pages", memory.initial, maximum));
+            }
+            memories.push(Memory::new(memory.initial, maximum));
+        }
+        self.memories = memories;
+        // Element expressions are evaluated once per instance. Active and
+        // declared segments are then dropped, which empties them, leaving only
+        // the passive ones available to `table.init`.
+        self.element_segments = Vec::new();
+        self.element_types = bytecode.element_segments.iter().map(|element_segment| element_segment.element_type).collect();
+        for element_segment in bytecode.element_segments.iter() {
+            let elements = element_segment.elements.iter()
+                .map(|element| Ok(Option::<u32>::from_slot(self.eval_const_expr(element)?)))
+                .collect::<Result<Vec<_>>>()?;
+            if let ElementKind::Active { table, offset } = &element_segment.kind {
+                let offset = self.eval_const_expr(offset)? as u32;
+                let table = self.tables.get_mut(*table as usize).ok_or_else(|| anyhow::anyhow!("Table not found"))?;
+                table.init(offset, &elements)?;
+            }
+            match element_segment.kind {
+                ElementKind::Passive => self.element_segments.push(elements),
+                _ => self.element_segments.push(Vec::new()),
+            }
+        }
+        // Active segments are dropped once copied into memory, leaving only the
+        // passive ones available to `memory.init`.
+        self.dropped_data = Vec::new();
+        for data_segment in bytecode.data_segments.iter() {
+            if let DataKind::Active { memory, offset } = &data_segment.kind {
+                let offset = self.eval_const_expr(offset)? as u32;
+                let memory = self.memories.get_mut(*memory as usize).ok_or_else(|| anyhow::anyhow!("Memory not found"))?;
+                memory.write(offset as u64, &data_segment.data)?;
+            }
+            self.dropped_data.push(matches!(data_segment.kind, DataKind::Active { .. }));
+        }
+        // The start function runs once everything else is initialized. If it
+        // traps the instance is unusable, so instantiation fails as a whole.
+        if let Some(index) = bytecode.start {
+            let function = bytecode.get_function_by_index(index as usize).ok_or_else(|| anyhow::anyhow!("Function not found"))?;
+            let height = self.stack.len();
+            let result = self.execute_fn(bytecode, function, imports);
+            self.stack.truncate(height);
+            self.write_shared_globals(imports);
+            result.context("Start function failed")?;
+        }
+        self.module = Some(bytecode.id());
+        Ok(())
+    }
+
+    // Hands a host object to the guest. The host holds one reference to the
+    // returned `externref` until it is released with `release_extern`.
+    pub fn new_extern(&mut self, object: Box<dyn Any>) -> Result<Value> {
+        let handle = self.externs.insert(object)?;
+        self.externs.retain(handle);
+        Ok(Value::extern_ref(Some(handle)))
+    }
+
+    pub fn extern_object(&self, value: &Value) -> Option<&dyn Any> {
+        self.externs.get(value.as_extern_ref()??).map(|object| &**object)
+    }
+
+    pub fn extern_object_mut(&mut self, value: &Value) -> Option<&mut dyn Any> {
+        self.externs.get_mut(value.as_extern_ref()??).map(|object| &mut **object)
+    }
+
+    // Takes another host reference to an object, such as one an import was
+    // passed and wants to keep. Unless retained, objects the guest no longer
+    // refers to are dropped when the host next calls in or releases one.
+    pub fn retain_extern(&mut self, value: &Value) {
+        if let Some(Some(handle)) = value.as_extern_ref() {
+            self.externs.retain(handle);
+        }
+    }
+
+    // Drops a reference held by the host. The object itself is dropped right
+    // away unless the guest still refers to it.
+    pub fn release_extern(&mut self, value: &Value) {
+        if let Some(Some(handle)) = value.as_extern_ref() {
+            self.externs.release(handle);
+            self.collect_externs(&[]);
+        }
+    }
+
+    // Takes another host reference to a caught exception, such as one an
+    // import was passed and wants to keep. Like objects, exceptions the host
+    // doesn't hold are dropped once the guest no longer refers to them.
+    pub fn retain_exn(&mut self, value: &Value) {
+        if let Some(Some(handle)) = value.as_exn_ref() {
+            self.exceptions.retain(handle);
+        }
+    }
+
+    // Drops a reference to an exception held by the host, as every `exnref`
+    // returned to it is.
+    pub fn release_exn(&mut self, value: &Value) {
+        if let Some(Some(handle)) = value.as_exn_ref() {
+            self.exceptions.release(handle);
+            self.collect_exceptions(self.references(ValType::ExnRef));
+            self.collect_externs(&[]);
+        }
+    }
+
+    // Objects are only collected between calls, when nothing is left on the
+    // operand stack. The guest then refers to them from globals, tables and
+    // the values of exceptions kept by an `exnref`, besides the arguments of
+    // the call about to start.
+    fn collect_externs(&mut self, args: &[Value]) {
+        let mut reachable = self.references(ValType::ExternRef);
+        let values = self.exceptions.objects().flat_map(|exception| exception.values()).chain(args);
+        reachable.extend(values.filter_map(|value| value.as_extern_ref().flatten()));
+        self.externs.collect(reachable);
+    }
+
+    // The non-null references of the given type held by globals, tables and
+    // element segments.
+    fn references(&self, ref_type: ValType) -> Vec<u32> {
+        let globals = self.globals.iter().zip(self.global_types.iter())
+            .filter(|(_, val_type)| **val_type == ref_type)
+            .filter_map(|(value, _)| Option::<u32>::from_slot(*value));
+        let tables = self.tables.iter()
+            .filter(|table| table.element_type() == ref_type)
+            .flat_map(|table| table.elements().iter().flatten().copied());
+        let element_segments = self.element_segments.iter().zip(self.element_types.iter())
+            .filter(|(_, val_type)| **val_type == ref_type)
+            .flat_map(|(elements, _)| elements.iter().flatten().copied());
+        globals.chain(tables).chain(element_segments).collect()
+    }
+
+    // Drops the caught exceptions neither the host nor `roots` lead to, where
+    // exceptions also lead to those their values refer to.
+    fn collect_exceptions(&mut self, roots: Vec<u32>) {
+        let mut pending: Vec<u32> = roots.into_iter().chain(self.exceptions.held()).collect();
+        let mut reachable = HashSet::new();
+        while let Some(handle) = pending.pop() {
+            if reachable.insert(handle) {
+                if let Some(exception) = self.exceptions.get(handle) {
+                    pending.extend(exception.values().iter().filter_map(|value| value.as_exn_ref().flatten()));
+                }
+            }
+        }
+        self.exceptions.collect(reachable);
+        self.exception_limit = (2 * self.exceptions.len()).max(MIN_EXCEPTION_LIMIT);
+    }
+
+    // Collects exceptions while the guest runs, so that catching in a loop
+    // doesn't grow them without bound. The operand stack doesn't record the
+    // types of its slots, so any slot that could be a handle is taken as one.
+    fn collect_running_exceptions(&mut self, frame: &Frame, callers: &[Frame]) {
+        let mut roots = self.references(ValType::ExnRef);
+        roots.extend(self.stack.iter().filter_map(|slot| Option::<u32>::from_slot(*slot)));
+        let locals = callers.iter().chain(std::iter::once(frame)).flat_map(|frame| frame.locals.iter());
+        roots.extend(locals.filter_map(|local| local.as_exn_ref().flatten()));
+        self.collect_exceptions(roots);
+    }
+
+    // Looks for a `try_table` in the frame that catches `error`, innermost
+    // first, and branches to its handler. Traps and exceptions that aren't
+    // caught here are passed on to the caller.
+    fn catch(&mut self, error: anyhow::Error, frame: &mut Frame, bytecode: &Bytecode) -> Result<()> {
+        let exception = error.downcast::<Exception>()?;
+        for index in (0..frame.labels.len()).rev() {
+            let label = frame.labels[index];
+            let Some(Instruction::TryTable { catches, .. }) = label.try_table.map(|pc| &frame.body[pc]) else {
+                continue;
+            };
+            for catch in catches.iter() {
+                let (label_depth, values, exnref) = match *catch {
+                    Catch::One { tag, label } | Catch::OneRef { tag, label } => {
+                        let tag = bytecode.tags.get(tag as usize).ok_or_else(|| anyhow::anyhow!("Tag not found"))?;
+                        if tag.tag != exception.tag() {
+                            continue;
+                        }
+                        if exception.values().iter().map(|value| value.val_type).ne(tag.params.iter().copied()) {
+                            return Err(anyhow::anyhow!("Exception values {:?} do not match tag parameters {:?}", exception.values(), tag.params));
+                        }
+                        (label, true, matches!(catch, Catch::OneRef { .. }))
+                    },
+                    Catch::All { label } => (label, false, false),
+                    Catch::AllRef { label } => (label, false, true),
+                };
+                frame.labels.truncate(index);
+                self.stack.truncate(label.height);
+                if values {
+                    self.stack.extend(exception.values().iter().map(|value| value.value));
+                }
+                if exnref {
+                    let handle = self.exceptions.insert(exception)?;
+                    self.push(Some(handle));
+                }
+                frame.pc = self.branch(frame, label_depth);
+                return Ok(());
+            }
+        }
+        Err(exception.into())
+    }
+
+    // Constant expressions may only read globals defined before the one being
+    // initialized, so they can be evaluated as instantiation goes.
+    fn eval_const_expr(&self, const_expr: &ConstExpr) -> Result<i128> {
+        let mut stack = Vec::new();
+        for instruction in const_expr.0.iter() {
+            match instruction {
+                Instruction::I32Const(value) => stack.push(*value as i128),
+                Instruction::I64Const(value) => stack.push(*value as i128),
+                Instruction::RefNull => stack.push(None::<u32>.into_slot()),
+                Instruction::RefFunc(index) => stack.push(Some(*index).into_slot()),
+                Instruction::F32Const(bits) => stack.push(*bits as i128),
+                Instruction::F64Const(bits) => stack.push(*bits as i128),
+                Instruction::V128Const(value) => stack.push(*value as i128),
+                Instruction::GlobalGet(index) => {
+                    let value = self.globals.get(*index as usize).ok_or_else(|| anyhow::anyhow!("Global not found"))?;
+                    stack.push(*value);
+                },
+                Instruction::I32Add | Instruction::I32Sub | Instruction::I32Mul => {
+                    let b = stack.pop().ok_or_else(|| anyhow::anyhow!("Invalid constant expression"))? as i32;
+                    let a = stack.pop().ok_or_else(|| anyhow::anyhow!("Invalid constant expression"))? as i32;
+                    let value = match instruction {
+                        Instruction::I32Add => a.wrapping_add(b),
+                        Instruction::I32Sub => a.wrapping_sub(b),
+                        _ => a.wrapping_mul(b),
+                    };
+                    stack.push(value as i128);
+                },
+                Instruction::I64Add | Instruction::I64Sub | Instruction::I64Mul => {
+                    let b = stack.pop().ok_or_else(|| anyhow::anyhow!("Invalid constant expression"))? as i64;
+                    let a = stack.pop().ok_or_else(|| anyhow::anyhow!("Invalid constant expression"))? as i64;
+                    let value = match instruction {
+                        Instruction::I64Add => a.wrapping_add(b),
+                        Instruction::I64Sub => a.wrapping_sub(b),
+                        _ => a.wrapping_mul(b),
+                    };
+                    stack.push(value as i128);
+                },
+                _ => return Err(anyhow::anyhow!("Instruction {:?} is not allowed in a constant expression", instruction)),
+            }
+        }
+        stack.pop().ok_or_else(|| anyhow::anyhow!("Invalid constant expression"))
     }
 
     pub fn run(&mut self, bytecode: &Bytecode, name: &str, imports: &mut Imports) -> Result<Return> {
-       let function = bytecode.get_function(name).ok_or(anyhow::anyhow!("Function not found"))?;
-       self.execute_fn(bytecode, function, imports)
+        self.call(bytecode, name, &[], imports)
+    }
+
+    // Every `externref` and `exnref` the call returns, directly or as a value
+    // of an uncaught exception, is retained for the host. The host owns those
+    // references and must hand each back with `release_extern` or
+    // `release_exn` once done, or the objects behind them are never dropped.
+    pub fn call(&mut self, bytecode: &Bytecode, name: &str, args: &[Value], imports: &mut Imports) -> Result<Return> {
+        // Functions index the globals, tables and memories of their own
+        // instance, so they only run in one made from the same module.
+        if self.module != Some(bytecode.id()) {
+            return Err(anyhow::anyhow!("The Vm was not instantiated from this module"));
+        }
+        let function = bytecode.get_function(name).ok_or_else(|| anyhow::anyhow!("Function not found"))?;
+        let params = &function.func_type.params;
+        if args.len() != params.len() {
+            return Err(anyhow::anyhow!("Function {} expects {} arguments but {} were given", name, params.len(), args.len()));
+        }
+        for (index, (arg, param)) in args.iter().zip(params.iter()).enumerate() {
+            if arg.val_type != *param {
+                return Err(anyhow::anyhow!("Argument {} of function {} has type {:?} but {:?} was expected", index, name, arg.val_type, param));
+            }
+        }
+        // Between calls the guest only refers to exceptions from globals,
+        // tables and the arguments it is about to get.
+        let mut roots = self.references(ValType::ExnRef);
+        roots.extend(args.iter().filter_map(|arg| arg.as_exn_ref().flatten()));
+        self.collect_exceptions(roots);
+        self.collect_externs(args);
+        self.read_shared_globals(imports);
+        // A trap leaves the operand stack wherever execution stopped, so it is
+        // reset for the next call.
+        let height = self.stack.len();
+        self.stack.extend(args.iter().map(|arg| arg.value));
+        let result = self.execute_fn(bytecode, function, imports);
+        if result.is_err() {
+            self.stack.truncate(height);
+        }
+        self.write_shared_globals(imports);
+        // The host now holds a reference to every object returned to it,
+        // including those carried by an uncaught exception.
+        let results = match &result {
+            Ok(Return::Single(value)) => std::slice::from_ref(value),
+            Ok(Return::Multiple(values)) => values.as_slice(),
+            Err(error) => error.downcast_ref::<Exception>().map_or(&[][..], |exception| exception.values()),
+            _ => &[],
+        };
+        for value in results {
+            if let Some(Some(handle)) = value.as_extern_ref() {
+                self.externs.retain(handle);
+            }
+            if let Some(Some(handle)) = value.as_exn_ref() {
+                self.exceptions.retain(handle);
+            }
+        }
+        result
+    }
+
+    // Picks up changes the host, or another instance, made to shared globals.
+    fn read_shared_globals(&mut self, imports: &Imports) {
+        for (index, module, name) in self.shared_globals.iter() {
+            if let Some(import) = imports.get_global(module, name) {
+                self.globals[*index] = import.value.value;
+            }
+        }
     }
 
-    fn execute_fn(&mut self, bytecode: &Bytecode, function: &Function, imports: &mut Imports) -> Result<Return> {
-        let args = function.func_type.params.iter().map(|val_type| Value { val_type: *val_type, value: self.stack.pop().unwrap() }).collect();
+    fn write_shared_globals(&self, imports: &mut Imports) {
+        for (index, module, name) in self.shared_globals.iter() {
+            if let Some(import) = imports.get_global_mut(module, name) {
+                import.value.value = self.globals[*index];
+            }
+        }
+    }
+
+    fn execute_fn<'a>(&mut self, bytecode: &'a Bytecode, function: &'a Function, imports: &mut Imports) -> Result<Return> {
         match &function.kind {
-            crate::FunctKind::Import { index } => {
-                let result = imports.invoke_import(*index as usize, args)?;
-                match result {
-                    Return::Single(value) => {
-                        self.stack.push(value.value);
+            crate::FunctKind::Import { index } => self.call_import(function, *index, imports)?,
+            crate::FunctKind::Definition(_) => {
+                let mut frame = self.enter(function)?;
+                let mut callers = Vec::new();
+                loop {
+                    if frame.labels.is_empty() {
+                        match callers.pop() {
+                            Some(caller) => frame = caller,
+                            None => break,
+                        }
+                        continue;
                     }
-                    Return::Multiple(values) => {
-                        self.stack.extend(values.into_iter().map(|value| value.value));
+                    if let Err(error) = self.step(&mut frame, &mut callers, bytecode, imports) {
+                        self.unwind(error, &mut frame, &mut callers, bytecode)?;
+                        if self.exceptions.len() >= self.exception_limit {
+                            self.collect_running_exceptions(&frame, &callers);
+                        }
                     }
-                    Return::Void => {}
                 }
             },
-            crate::FunctKind::Definition(function_definition) => {
-                let mut locals = function_definition.locals.iter().map(|local| Value { val_type: *local, value: 0 }).collect();
-                for instruction in function_definition.body.iter() {
-                    self.execute_instruction(instruction, &mut locals, bytecode, imports)?;
+        }
+
+        // Results sit on top of the stack with the last one uppermost.
+        let returns = &function.func_type.returns;
+        let results_start = self.stack.len().checked_sub(returns.len()).ok_or(Trap::new(TrapKind::StackUnderflow))?;
+        let mut results: Vec<Value> = self.stack.drain(results_start..).zip(returns.iter()).map(|(value, val_type)| Value { val_type: *val_type, value }).collect();
+        match results.len() {
+            0 => Ok(Return::Void),
+            1 => Ok(Return::Single(results.remove(0))),
+            _ => Ok(Return::Multiple(results)),
+        }
+    }
+
+    // Runs the next instruction of `frame`, entering the callee of a call. The
+    // caller's frame is kept on `callers`, unless the call is a tail call.
+    fn step<'a>(&mut self, frame: &mut Frame<'a>, callers: &mut Vec<Frame<'a>>, bytecode: &'a Bytecode, imports: &mut Imports) -> Result<()> {
+        let instruction = &frame.body[frame.pc];
+        frame.pc += 1;
+        let (index, tail_call) = match self.execute_instruction(instruction, frame, bytecode)? {
+            Control::Continue => return Ok(()),
+            Control::Call(index) => (index, false),
+            Control::TailCall(index) => (index, true),
+        };
+        let function = bytecode.get_function_by_index(index as usize).ok_or_else(|| anyhow::anyhow!("Function not found"))?;
+        match &function.kind {
+            crate::FunctKind::Import { index } => self.call_import(function, *index, imports)?,
+            crate::FunctKind::Definition(_) if tail_call => *frame = self.enter(function)?,
+            crate::FunctKind::Definition(_) => {
+                if callers.len() + 1 >= self.config.max_call_depth {
+                    return Err(Trap::new(TrapKind::StackExhausted).into());
                 }
+                let callee = self.enter(function)?;
+                callers.push(std::mem::replace(frame, callee));
             },
         }
+        Ok(())
+    }
+
+    // Passes `error` to the innermost frame that catches it, dropping the
+    // frames it unwinds through. Traps are never caught.
+    fn unwind<'a>(&mut self, mut error: anyhow::Error, frame: &mut Frame<'a>, callers: &mut Vec<Frame<'a>>, bytecode: &Bytecode) -> Result<()> {
+        loop {
+            error = match self.catch(error, frame, bytecode) {
+                Ok(()) => return Ok(()),
+                Err(error) => error,
+            };
+            match callers.pop() {
+                Some(caller) if error.is::<Exception>() => *frame = caller,
+                _ => return Err(error),
+            }
+        }
+    }
+
+    // Builds the frame of a defined function, taking its arguments off the
+    // operand stack.
+    fn enter<'a>(&mut self, function: &'a Function) -> Result<Frame<'a>> {
+        let crate::FunctKind::Definition(function_definition) = &function.kind else {
+            return Err(anyhow::anyhow!("Function is not defined by the module"));
+        };
+        let params = &function.func_type.params;
+        let args_start = self.stack.len().checked_sub(params.len()).ok_or(Trap::new(TrapKind::StackUnderflow))?;
+        // Parameters take the first local indices, in declaration order,
+        // followed by the zero-initialized declared locals.
+        let mut locals: Vec<Value> = self.stack.drain(args_start..).zip(params.iter()).map(|(value, val_type)| Value { val_type: *val_type, value }).collect();
+        locals.extend(function_definition.locals.iter().map(|local| Value::default_for(*local)));
+        // The function body is the outermost block: branching to it, or
+        // reaching its final `end`, returns from the function.
+        let body_label = Label {
+            arity: function.func_type.returns.len(),
+            height: self.stack.len(),
+            target: function_definition.body.len() - 1,
+            try_table: None,
+        };
+        Ok(Frame { body: &function_definition.body, pc: 0, locals, labels: vec![body_label] })
+    }
+
+    // Calls a host function with the arguments on top of the operand stack and
+    // pushes its results in their place.
+    fn call_import(&mut self, function: &Function, index: u32, imports: &mut Imports) -> Result<()> {
+        let params = &function.func_type.params;
+        let args_start = self.stack.len().checked_sub(params.len()).ok_or(Trap::new(TrapKind::StackUnderflow))?;
+        let args: Vec<Value> = self.stack.drain(args_start..).zip(params.iter()).map(|(value, val_type)| Value { val_type: *val_type, value }).collect();
+        let values = match imports.invoke_import(index as usize, args)? {
+            Return::Void => Vec::new(),
+            Return::Single(value) => vec![value],
+            Return::Multiple(values) => values,
+        };
+        let returns = &function.func_type.returns;
+        if values.len() != returns.len() || values.iter().zip(returns.iter()).any(|(value, val_type)| value.val_type != *val_type) {
+            return Err(anyhow::anyhow!("Import returned {:?} but {:?} was expected", values, returns));
+        }
+        self.stack.extend(values.into_iter().map(|value| value.value));
+        Ok(())
+    }
+
+    // Pops the table element of an indirect call and returns the function it
+    // refers to, once checked against the expected type.
+    fn indirect_callee(&mut self, bytecode: &Bytecode, type_index: u32, table: u32) -> Result<u32> {
+        let element = self.pop::<u32>()?;
+        let index = self.table(table)?.get(element)?.ok_or(Trap::new(TrapKind::IndirectCallToNull))?;
+        let function = bytecode.get_function_by_index(index as usize).ok_or_else(|| anyhow::anyhow!("Function not found"))?;
+        if bytecode.get_function_type(type_index as usize) != Some(&function.func_type) {
+            return Err(Trap::new(TrapKind::IndirectCallTypeMismatch).into());
+        }
+        Ok(index)
+    }
+
+    // Leaves only the callee's arguments above the frame and ends it, for the
+    // run loop to call the callee in its place.
+    fn tail_call(&mut self, bytecode: &Bytecode, frame: &mut Frame, index: u32) -> Result<Control> {
+        let function = bytecode.get_function_by_index(index as usize).ok_or_else(|| anyhow::anyhow!("Function not found"))?;
+        let args_start = self.stack.len().checked_sub(function.func_type.params.len()).ok_or(Trap::new(TrapKind::StackUnderflow))?;
+        self.stack.drain(frame.labels[0].height..args_start);
+        frame.labels.clear();
+        Ok(Control::TailCall(index))
+    }
+
+    // Unwinds the operand stack to the label `depth` levels out, keeping the
+    // values carried by the branch, and returns the instruction to continue at.
+    // The target label itself stays in place: blocks continue at their `end`,
+    // which pops it, and loops continue at their first instruction.
+    fn branch(&mut self, frame: &mut Frame, depth: u32) -> usize {
+        let label = frame.labels[frame.labels.len() - 1 - depth as usize];
+        let values_start = self.stack.len() - label.arity;
+        self.stack.drain(label.height..values_start);
+        frame.labels.truncate(frame.labels.len() - depth as usize);
+        label.target
+    }
+
+    fn label_height(&self, params: u32) -> Result<usize> {
+        let height = self.stack.len().checked_sub(params as usize).ok_or(Trap::new(TrapKind::StackUnderflow))?;
+        Ok(height)
+    }
+
+    fn pop<T: StackValue>(&mut self) -> Result<T> {
+        let slot = self.stack.pop().ok_or(Trap::new(TrapKind::StackUnderflow))?;
+        Ok(T::from_slot(slot))
+    }
+
+    fn push<T: StackValue>(&mut self, value: T) {
+        self.stack.push(value.into_slot());
+    }
+
+    fn unary<T: StackValue>(&mut self, op: impl FnOnce(T) -> T) -> Result<()> {
+        let a = self.pop()?;
+        self.push(op(a));
+        Ok(())
+    }
+
+    fn binary<T: StackValue>(&mut self, op: impl FnOnce(T, T) -> T) -> Result<()> {
+        let b = self.pop()?;
+        let a = self.pop()?;
+        self.push(op(a, b));
+        Ok(())
+    }
+
+    fn compare<T: StackValue>(&mut self, op: impl FnOnce(T, T) -> bool) -> Result<()> {
+        let b = self.pop()?;
+        let a = self.pop()?;
+        self.push(op(a, b) as i32);
+        Ok(())
+    }
+
+    fn lanewise_unary<T: Lane>(&mut self, op: impl Fn(T) -> T) -> Result<()> {
+        self.unary::<u128>(|a| from_lanes(lanes::<T>(a).map(op)))
+    }
+
+    fn lanewise_binary<T: Lane>(&mut self, op: impl Fn(T, T) -> T) -> Result<()> {
+        self.binary::<u128>(|a, b| from_lanes(lanes::<T>(a).zip(lanes::<T>(b)).map(|(a, b)| op(a, b))))
+    }
+
+    fn lanewise_ternary<T: Lane>(&mut self, op: impl Fn(T, T, T) -> T) -> Result<()> {
+        let c = self.pop::<u128>()?;
+        self.binary::<u128>(|a, b| from_lanes(lanes::<T>(a).zip(lanes::<T>(b)).zip(lanes::<T>(c)).map(|((a, b), c)| op(a, b, c))))
+    }
+
+    fn lanewise_compare<T: Lane>(&mut self, op: impl Fn(T, T) -> bool) -> Result<()> {
+        self.binary::<u128>(|a, b| simd::compare(a, b, op))
+    }
+
+    // Shift amounts are taken modulo the lane width, which the `wrapping_`
+    // shifts do already.
+    fn lanewise_shift<T: Lane>(&mut self, op: impl Fn(T, u32) -> T) -> Result<()> {
+        let amount = self.pop::<u32>()?;
+        self.lanewise_unary::<T>(|a| op(a, amount))
+    }
+
+    fn divide<T: StackValue + Default + PartialEq>(&mut self, op: impl FnOnce(T, T) -> Option<T>) -> Result<()> {
+        let b = self.pop()?;
+        let a = self.pop()?;
+        if b == T::default() {
+            return Err(Trap::new(TrapKind::IntegerDivideByZero).into());
+        }
+        let value = op(a, b).ok_or(Trap::new(TrapKind::IntegerOverflow))?;
+        self.push(value);
+        Ok(())
+    }
+
+    fn convert<A: StackValue, B: StackValue>(&mut self, op: impl FnOnce(A) -> B) -> Result<()> {
+        let a = self.pop()?;
+        self.push(op(a));
+        Ok(())
+    }
+
+    fn try_convert<A: StackValue, B: StackValue>(&mut self, op: impl FnOnce(A) -> Result<B>) -> Result<()> {
+        let a = self.pop()?;
+        self.push(op(a)?);
+        Ok(())
+    }
+
+    fn memory(&mut self, index: u32) -> Result<&mut Memory> {
+        self.memories.get_mut(index as usize).ok_or_else(|| anyhow::anyhow!("Memory not found"))
+    }
+
+    fn table(&mut self, index: u32) -> Result<&mut Table> {
+        self.tables.get_mut(index as usize).ok_or_else(|| anyhow::anyhow!("Table not found"))
+    }
+
+    fn load<const N: usize>(&mut self, memarg: &MemArg) -> Result<[u8; N]> {
+        let address = self.pop::<u32>()? as u64 + memarg.offset;
+        self.memory(memarg.memory)?.read(address)
+    }
+
+    fn store(&mut self, memarg: &MemArg, bytes: &[u8]) -> Result<()> {
+        let address = self.pop::<u32>()? as u64 + memarg.offset;
+        self.memory(memarg.memory)?.write(address, bytes)
+    }
+
+    fn memory_init(&mut self, bytecode: &Bytecode, data: u32, memory: u32) -> Result<()> {
+        let len = self.pop::<u32>()? as usize;
+        let source = self.pop::<u32>()? as usize;
+        let destination = self.pop::<u32>()?;
+        let segment = match self.dropped_data.get(data as usize) {
+            Some(false) => &bytecode.data_segments[data as usize].data[..],
+            Some(true) => &[],
+            None => return Err(anyhow::anyhow!("Data segment not found")),
+        };
+        let bytes = source.checked_add(len).and_then(|end| segment.get(source..end)).ok_or(Trap::new(TrapKind::MemoryOutOfBounds))?;
+        self.memory(memory)?.write(destination as u64, bytes)
+    }
+
+    fn memory_copy(&mut self, destination: u32, source: u32) -> Result<()> {
+        let len = self.pop::<u32>()? as usize;
+        let source_address = self.pop::<u32>()? as u64;
+        let destination_address = self.pop::<u32>()? as u64;
+        if destination == source {
+            self.memory(destination)?.copy_within(destination_address, source_address, len)
+        } else {
+            let bytes = self.memory(source)?.slice(source_address, len)?.to_vec();
+            self.memory(destination)?.write(destination_address, &bytes)
+        }
+    }
 
-        if function.func_type.returns.is_empty() {
-            Ok(Return::Void)
-        } else if function.func_type.returns.len() == 1 {
-            Ok(Return::Single(Value { val_type: function.func_type.returns[0], value: self.stack.pop().unwrap() }))
+    fn table_copy(&mut self, destination: u32, source: u32) -> Result<()> {
+        let len = self.pop::<u32>()?;
+        let source_index = self.pop::<u32>()?;
+        let destination_index = self.pop::<u32>()?;
+        if destination == source {
+            self.table(destination)?.copy_within(destination_index, source_index, len)
         } else {
-            Ok(Return::Multiple(function.func_type.returns.iter().map(|val_type| Value { val_type: *val_type, value: self.stack.pop().unwrap() }).collect()))
+            let elements = self.table(source)?.slice(source_index, len)?.to_vec();
+            self.table(destination)?.init(destination_index, &elements)
         }
     }
 
-    fn execute_instruction(&mut self,  instruction: &Instruction, locals: &mut Vec<Value>, bytecode: &Bytecode, imports: &mut Imports) -> Result<()> {
+    fn table_init(&mut self, element: u32, table: u32) -> Result<()> {
+        let len = self.pop::<u32>()? as usize;
+        let source = self.pop::<u32>()? as usize;
+        let destination = self.pop::<u32>()?;
+        let segment = self.element_segments.get(element as usize).ok_or_else(|| anyhow::anyhow!("Element segment not found"))?;
+        let elements = source.checked_add(len).and_then(|end| segment.get(source..end)).ok_or(Trap::new(TrapKind::TableOutOfBounds))?;
+        let table = self.tables.get_mut(table as usize).ok_or_else(|| anyhow::anyhow!("Table not found"))?;
+        table.init(destination, elements)
+    }
+
+    fn execute_instruction(&mut self,  instruction: &Instruction, frame: &mut Frame, bytecode: &Bytecode) -> Result<Control> {
         match instruction {
-            Instruction::I32Add => {
-                let b = self.stack.pop().unwrap() as i32;
-                let a = self.stack.pop().unwrap() as i32;
-                self.stack.push((a+b) as i64);
-            },
-            Instruction::I32Sub => {
-               let b = self.stack.pop().unwrap() as i32;
-               let a = self.stack.pop().unwrap() as i32;
-               self.stack.push((a-b) as i64);
-            },
-            Instruction::I32Mul => {
-                let b = self.stack.pop().unwrap() as i32;
-                let a = self.stack.pop().unwrap() as i32;
-                self.stack.push((a*b) as i64);
-            },
-            Instruction::I32Div => {
-                let b = self.stack.pop().unwrap() as i32;
-                let a = self.stack.pop().unwrap() as i32;
-                self.stack.push((a/b) as i64);
-            },
-            Instruction::I32Rem => {
-                let b = self.stack.pop().unwrap() as i32;
-                let a = self.stack.pop().unwrap() as i32;
-                self.stack.push((a%b) as i64);
-            },
-            Instruction::I32And => {
-                let b = self.stack.pop().unwrap() as i32;
-                let a = self.stack.pop().unwrap() as i32;
-                self.stack.push((a&b) as i64);
-            },
-            Instruction::I32Or => {
-                let b = self.stack.pop().unwrap() as i32;
-                let a = self.stack.pop().unwrap() as i32;
-                self.stack.push((a|b) as i64);
-            },
-            Instruction::I32Xor => {
-                let b = self.stack.pop().unwrap() as i32;
-                let a = self.stack.pop().unwrap() as i32;
-                self.stack.push((a^b) as i64);
-            },
-            Instruction::I32Shl => {
-                let b = self.stack.pop().unwrap() as i32;
-                let a = self.stack.pop().unwrap() as i32;
-                self.stack.push((a<<b) as i64);
-            },
+            Instruction::I32Add => self.binary::<i32>(i32::wrapping_add)?,
+            Instruction::I32Sub => self.binary::<i32>(i32::wrapping_sub)?,
+            Instruction::I32Mul => self.binary::<i32>(i32::wrapping_mul)?,
+            Instruction::I32DivS => self.divide::<i32>(i32::checked_div)?,
+            Instruction::I32DivU => self.divide::<u32>(u32::checked_div)?,
+            Instruction::I32RemS => self.divide::<i32>(|a, b| Some(a.wrapping_rem(b)))?,
+            Instruction::I32RemU => self.divide::<u32>(u32::checked_rem)?,
+            Instruction::I32And => self.binary::<i32>(|a, b| a & b)?,
+            Instruction::I32Or => self.binary::<i32>(|a, b| a | b)?,
+            Instruction::I32Xor => self.binary::<i32>(|a, b| a ^ b)?,
+            Instruction::I32Shl => self.binary::<i32>(|a, b| a.wrapping_shl(b as u32))?,
+            Instruction::I32ShrS => self.binary::<i32>(|a, b| a.wrapping_shr(b as u32))?,
+            Instruction::I32ShrU => self.binary::<u32>(u32::wrapping_shr)?,
+            Instruction::I32Rotl => self.binary::<u32>(|a, b| a.rotate_left(b & 31))?,
+            Instruction::I32Rotr => self.binary::<u32>(|a, b| a.rotate_right(b & 31))?,
+            Instruction::I32Clz => self.unary::<u32>(u32::leading_zeros)?,
+            Instruction::I32Ctz => self.unary::<u32>(u32::trailing_zeros)?,
+            Instruction::I32Popcnt => self.unary::<u32>(u32::count_ones)?,
+            Instruction::I32Extend8S => self.unary::<i32>(|a| a as i8 as i32)?,
+            Instruction::I32Extend16S => self.unary::<i32>(|a| a as i16 as i32)?,
+            Instruction::I32Eqz => self.unary::<i32>(|a| (a == 0) as i32)?,
+            Instruction::I32Eq => self.compare::<i32>(|a, b| a == b)?,
+            Instruction::I32Ne => self.compare::<i32>(|a, b| a != b)?,
+            Instruction::I32LtS => self.compare::<i32>(|a, b| a < b)?,
+            Instruction::I32LtU => self.compare::<u32>(|a, b| a < b)?,
+            Instruction::I32GtS => self.compare::<i32>(|a, b| a > b)?,
+            Instruction::I32GtU => self.compare::<u32>(|a, b| a > b)?,
+            Instruction::I32LeS => self.compare::<i32>(|a, b| a <= b)?,
+            Instruction::I32LeU => self.compare::<u32>(|a, b| a <= b)?,
+            Instruction::I32GeS => self.compare::<i32>(|a, b| a >= b)?,
+            Instruction::I32GeU => self.compare::<u32>(|a, b| a >= b)?,
             Instruction::I32Const(value) => {
-                self.stack.push(*value as i64);
-            },
-            Instruction::Call(index) => {
-                let function = bytecode.get_function_by_index(*index as usize).ok_or(anyhow::anyhow!("Function not found"))?;
-                let result = self.execute_fn(bytecode, function, imports)?;
-                match result {
-                    Return::Single(value) => {
-                        self.stack.push(value.value);
-                    }
-                    Return::Multiple(values) => {
-                        self.stack.extend(values.into_iter().map(|value| value.value));
-                    }
-                    Return::Void => {}
-                }
+                self.stack.push(*value as i128);
+            },
+            Instruction::I64Const(value) => {
+                self.stack.push(*value as i128);
+            },
+            Instruction::I64Add => self.binary::<i64>(i64::wrapping_add)?,
+            Instruction::I64Sub => self.binary::<i64>(i64::wrapping_sub)?,
+            Instruction::I64Mul => self.binary::<i64>(i64::wrapping_mul)?,
+            Instruction::I64DivS => self.divide::<i64>(i64::checked_div)?,
+            Instruction::I64DivU => self.divide::<i64>(|a, b| Some((a as u64 / b as u64) as i64))?,
+            Instruction::I64RemS => self.divide::<i64>(|a, b| Some(a.wrapping_rem(b)))?,
+            Instruction::I64RemU => self.divide::<i64>(|a, b| Some((a as u64 % b as u64) as i64))?,
+            Instruction::I64And => self.binary::<i64>(|a, b| a & b)?,
+            Instruction::I64Or => self.binary::<i64>(|a, b| a | b)?,
+            Instruction::I64Xor => self.binary::<i64>(|a, b| a ^ b)?,
+            Instruction::I64Shl => self.binary::<i64>(|a, b| a.wrapping_shl(b as u32))?,
+            Instruction::I64ShrS => self.binary::<i64>(|a, b| a.wrapping_shr(b as u32))?,
+            Instruction::I64ShrU => self.binary::<i64>(|a, b| (a as u64).wrapping_shr(b as u32) as i64)?,
+            Instruction::I64Rotl => self.binary::<i64>(|a, b| a.rotate_left((b & 63) as u32))?,
+            Instruction::I64Rotr => self.binary::<i64>(|a, b| a.rotate_right((b & 63) as u32))?,
+            Instruction::I64Clz => self.unary::<i64>(|a| a.leading_zeros() as i64)?,
+            Instruction::I64Ctz => self.unary::<i64>(|a| a.trailing_zeros() as i64)?,
+            Instruction::I64Popcnt => self.unary::<i64>(|a| a.count_ones() as i64)?,
+            Instruction::I64Extend8S => self.unary::<i64>(|a| a as i8 as i64)?,
+            Instruction::I64Extend16S => self.unary::<i64>(|a| a as i16 as i64)?,
+            Instruction::I64Extend32S => self.unary::<i64>(|a| a as i32 as i64)?,
+            Instruction::I64Eqz => self.unary::<i64>(|a| (a == 0) as i64)?,
+            Instruction::I64Eq => self.compare::<i64>(|a, b| a == b)?,
+            Instruction::I64Ne => self.compare::<i64>(|a, b| a != b)?,
+            Instruction::I64LtS => self.compare::<i64>(|a, b| a < b)?,
+            Instruction::I64LtU => self.compare::<i64>(|a, b| (a as u64) < b as u64)?,
+            Instruction::I64GtS => self.compare::<i64>(|a, b| a > b)?,
+            Instruction::I64GtU => self.compare::<i64>(|a, b| a as u64 > b as u64)?,
+            Instruction::I64LeS => self.compare::<i64>(|a, b| a <= b)?,
+            Instruction::I64LeU => self.compare::<i64>(|a, b| a as u64 <= b as u64)?,
+            Instruction::I64GeS => self.compare::<i64>(|a, b| a >= b)?,
+            Instruction::I64GeU => self.compare::<i64>(|a, b| a as u64 >= b as u64)?,
+            Instruction::I32WrapI64 => self.unary::<i64>(|a| a as i32 as i64)?,
+            Instruction::I64ExtendI32S => self.unary::<i64>(|a| a as i32 as i64)?,
+            Instruction::I64ExtendI32U => self.unary::<i64>(|a| a as u32 as i64)?,
+            Instruction::F32Const(bits) => {
+                self.stack.push(*bits as i128);
+            },
+            Instruction::F32Abs => self.unary::<f32>(f32::abs)?,
+            Instruction::F32Neg => self.unary::<f32>(|a| -a)?,
+            Instruction::F32Ceil => self.unary::<f32>(|a| canonicalize_f32(a.ceil()))?,
+            Instruction::F32Floor => self.unary::<f32>(|a| canonicalize_f32(a.floor()))?,
+            Instruction::F32Trunc => self.unary::<f32>(|a| canonicalize_f32(a.trunc()))?,
+            Instruction::F32Nearest => self.unary::<f32>(|a| canonicalize_f32(a.round_ties_even()))?,
+            Instruction::F32Sqrt => self.unary::<f32>(|a| canonicalize_f32(a.sqrt()))?,
+            Instruction::F32Add => self.binary::<f32>(|a, b| canonicalize_f32(a + b))?,
+            Instruction::F32Sub => self.binary::<f32>(|a, b| canonicalize_f32(a - b))?,
+            Instruction::F32Mul => self.binary::<f32>(|a, b| canonicalize_f32(a * b))?,
+            Instruction::F32Div => self.binary::<f32>(|a, b| canonicalize_f32(a / b))?,
+            Instruction::F32Min => self.binary::<f32>(|a, b| canonicalize_f32(f32_min(a, b)))?,
+            Instruction::F32Max => self.binary::<f32>(|a, b| canonicalize_f32(f32_max(a, b)))?,
+            Instruction::F32Copysign => self.binary::<f32>(f32::copysign)?,
+            Instruction::F32Eq => self.compare::<f32>(|a, b| a == b)?,
+            Instruction::F32Ne => self.compare::<f32>(|a, b| a != b)?,
+            Instruction::F32Lt => self.compare::<f32>(|a, b| a < b)?,
+            Instruction::F32Gt => self.compare::<f32>(|a, b| a > b)?,
+            Instruction::F32Le => self.compare::<f32>(|a, b| a <= b)?,
+            Instruction::F32Ge => self.compare::<f32>(|a, b| a >= b)?,
+            Instruction::F64Const(bits) => {
+                self.stack.push(*bits as i128);
+            },
+            Instruction::F64Abs => self.unary::<f64>(f64::abs)?,
+            Instruction::F64Neg => self.unary::<f64>(|a| -a)?,
+            Instruction::F64Ceil => self.unary::<f64>(|a| canonicalize_f64(a.ceil()))?,
+            Instruction::F64Floor => self.unary::<f64>(|a| canonicalize_f64(a.floor()))?,
+            Instruction::F64Trunc => self.unary::<f64>(|a| canonicalize_f64(a.trunc()))?,
+            Instruction::F64Nearest => self.unary::<f64>(|a| canonicalize_f64(a.round_ties_even()))?,
+            Instruction::F64Sqrt => self.unary::<f64>(|a| canonicalize_f64(a.sqrt()))?,
+            Instruction::F64Add => self.binary::<f64>(|a, b| canonicalize_f64(a + b))?,
+            Instruction::F64Sub => self.binary::<f64>(|a, b| canonicalize_f64(a - b))?,
+            Instruction::F64Mul => self.binary::<f64>(|a, b| canonicalize_f64(a * b))?,
+            Instruction::F64Div => self.binary::<f64>(|a, b| canonicalize_f64(a / b))?,
+            Instruction::F64Min => self.binary::<f64>(|a, b| canonicalize_f64(f64_min(a, b)))?,
+            Instruction::F64Max => self.binary::<f64>(|a, b| canonicalize_f64(f64_max(a, b)))?,
+            Instruction::F64Copysign => self.binary::<f64>(f64::copysign)?,
+            Instruction::F64Eq => self.compare::<f64>(|a, b| a == b)?,
+            Instruction::F64Ne => self.compare::<f64>(|a, b| a != b)?,
+            Instruction::F64Lt => self.compare::<f64>(|a, b| a < b)?,
+            Instruction::F64Gt => self.compare::<f64>(|a, b| a > b)?,
+            Instruction::F64Le => self.compare::<f64>(|a, b| a <= b)?,
+            Instruction::F64Ge => self.compare::<f64>(|a, b| a >= b)?,
+            Instruction::I32TruncF32S => self.try_convert(|a: f32| Ok(checked_trunc(a as f64, -2147483648.0, 2147483648.0)? as i32))?,
+            Instruction::I32TruncF32U => self.try_convert(|a: f32| Ok(checked_trunc(a as f64, 0.0, 4294967296.0)? as u32))?,
+            Instruction::I32TruncF64S => self.try_convert(|a: f64| Ok(checked_trunc(a, -2147483648.0, 2147483648.0)? as i32))?,
+            Instruction::I32TruncF64U => self.try_convert(|a: f64| Ok(checked_trunc(a, 0.0, 4294967296.0)? as u32))?,
+            Instruction::I64TruncF32S => self.try_convert(|a: f32| Ok(checked_trunc(a as f64, -9223372036854775808.0, 9223372036854775808.0)? as i64))?,
+            Instruction::I64TruncF32U => self.try_convert(|a: f32| Ok(checked_trunc(a as f64, 0.0, 18446744073709551616.0)? as u64))?,
+            Instruction::I64TruncF64S => self.try_convert(|a: f64| Ok(checked_trunc(a, -9223372036854775808.0, 9223372036854775808.0)? as i64))?,
+            Instruction::I64TruncF64U => self.try_convert(|a: f64| Ok(checked_trunc(a, 0.0, 18446744073709551616.0)? as u64))?,
+            Instruction::I32TruncSatF32S => self.convert(|a: f32| a as i32)?,
+            Instruction::I32TruncSatF32U => self.convert(|a: f32| a as u32)?,
+            Instruction::I32TruncSatF64S => self.convert(|a: f64| a as i32)?,
+            Instruction::I32TruncSatF64U => self.convert(|a: f64| a as u32)?,
+            Instruction::I64TruncSatF32S => self.convert(|a: f32| a as i64)?,
+            Instruction::I64TruncSatF32U => self.convert(|a: f32| a as u64)?,
+            Instruction::I64TruncSatF64S => self.convert(|a: f64| a as i64)?,
+            Instruction::I64TruncSatF64U => self.convert(|a: f64| a as u64)?,
+            Instruction::F32ConvertI32S => self.convert(|a: i32| a as f32)?,
+            Instruction::F32ConvertI32U => self.convert(|a: u32| a as f32)?,
+            Instruction::F32ConvertI64S => self.convert(|a: i64| a as f32)?,
+            Instruction::F32ConvertI64U => self.convert(|a: u64| a as f32)?,
+            Instruction::F32DemoteF64 => self.convert(|a: f64| canonicalize_f32(a as f32))?,
+            Instruction::F64ConvertI32S => self.convert(|a: i32| a as f64)?,
+            Instruction::F64ConvertI32U => self.convert(|a: u32| a as f64)?,
+            Instruction::F64ConvertI64S => self.convert(|a: i64| a as f64)?,
+            Instruction::F64ConvertI64U => self.convert(|a: u64| a as f64)?,
+            Instruction::F64PromoteF32 => self.convert(|a: f32| canonicalize_f64(a as f64))?,
+            Instruction::I32ReinterpretF32 => self.convert(|a: f32| a.to_bits())?,
+            Instruction::I64ReinterpretF64 => self.convert(|a: f64| a.to_bits())?,
+            Instruction::F32ReinterpretI32 => self.convert(f32::from_bits)?,
+            Instruction::F64ReinterpretI64 => self.convert(f64::from_bits)?,
+            Instruction::V128Const(value) => self.push(*value),
+            Instruction::V128Load(memarg) => {
+                let value = u128::from_le_bytes(self.load(memarg)?);
+                self.push(value);
+            },
+            Instruction::V128Load8x8S(memarg) => {
+                let bytes = u64::from_le_bytes(self.load(memarg)?) as u128;
+                self.push(from_lanes(lanes::<i8>(bytes).take(8).map(i16::from)));
+            },
+            Instruction::V128Load8x8U(memarg) => {
+                let bytes = u64::from_le_bytes(self.load(memarg)?) as u128;
+                self.push(from_lanes(lanes::<u8>(bytes).take(8).map(u16::from)));
+            },
+            Instruction::V128Load16x4S(memarg) => {
+                let bytes = u64::from_le_bytes(self.load(memarg)?) as u128;
+                self.push(from_lanes(lanes::<i16>(bytes).take(4).map(i32::from)));
+            },
+            Instruction::V128Load16x4U(memarg) => {
+                let bytes = u64::from_le_bytes(self.load(memarg)?) as u128;
+                self.push(from_lanes(lanes::<u16>(bytes).take(4).map(u32::from)));
+            },
+            Instruction::V128Load32x2S(memarg) => {
+                let bytes = u64::from_le_bytes(self.load(memarg)?) as u128;
+                self.push(from_lanes(lanes::<i32>(bytes).take(2).map(i64::from)));
+            },
+            Instruction::V128Load32x2U(memarg) => {
+                let bytes = u64::from_le_bytes(self.load(memarg)?) as u128;
+                self.push(from_lanes(lanes::<u32>(bytes).take(2).map(u64::from)));
+            },
+            Instruction::V128Load8Splat(memarg) => {
+                let value = u8::from_le_bytes(self.load(memarg)?);
+                self.push(splat(value));
+            },
+            Instruction::V128Load16Splat(memarg) => {
+                let value = u16::from_le_bytes(self.load(memarg)?);
+                self.push(splat(value));
+            },
+            Instruction::V128Load32Splat(memarg) => {
+                let value = u32::from_le_bytes(self.load(memarg)?);
+                self.push(splat(value));
+            },
+            Instruction::V128Load64Splat(memarg) => {
+                let value = u64::from_le_bytes(self.load(memarg)?);
+                self.push(splat(value));
+            },
+            Instruction::V128Load32Zero(memarg) => {
+                let value = u32::from_le_bytes(self.load(memarg)?);
+                self.push(value as u128);
+            },
+            Instruction::V128Load64Zero(memarg) => {
+                let value = u64::from_le_bytes(self.load(memarg)?);
+                self.push(value as u128);
+            },
+            Instruction::V128Load8Lane { memarg, lane } => {
+                let vector = self.pop::<u128>()?;
+                let value = u8::from_le_bytes(self.load(memarg)?);
+                self.push(replace_lane(vector, *lane as usize, value));
+            },
+            Instruction::V128Load16Lane { memarg, lane } => {
+                let vector = self.pop::<u128>()?;
+                let value = u16::from_le_bytes(self.load(memarg)?);
+                self.push(replace_lane(vector, *lane as usize, value));
+            },
+            Instruction::V128Load32Lane { memarg, lane } => {
+                let vector = self.pop::<u128>()?;
+                let value = u32::from_le_bytes(self.load(memarg)?);
+                self.push(replace_lane(vector, *lane as usize, value));
+            },
+            Instruction::V128Load64Lane { memarg, lane } => {
+                let vector = self.pop::<u128>()?;
+                let value = u64::from_le_bytes(self.load(memarg)?);
+                self.push(replace_lane(vector, *lane as usize, value));
+            },
+            Instruction::V128Store(memarg) => {
+                let value = self.pop::<u128>()?;
+                self.store(memarg, &value.to_le_bytes())?;
+            },
+            Instruction::V128Store8Lane { memarg, lane } => {
+                let vector = self.pop::<u128>()?;
+                self.store(memarg, &simd::lane::<u8>(vector, *lane as usize).to_le_bytes())?;
+            },
+            Instruction::V128Store16Lane { memarg, lane } => {
+                let vector = self.pop::<u128>()?;
+                self.store(memarg, &simd::lane::<u16>(vector, *lane as usize).to_le_bytes())?;
+            },
+            Instruction::V128Store32Lane { memarg, lane } => {
+                let vector = self.pop::<u128>()?;
+                self.store(memarg, &simd::lane::<u32>(vector, *lane as usize).to_le_bytes())?;
+            },
+            Instruction::V128Store64Lane { memarg, lane } => {
+                let vector = self.pop::<u128>()?;
+                self.store(memarg, &simd::lane::<u64>(vector, *lane as usize).to_le_bytes())?;
+            },
+            Instruction::I8x16Shuffle(indices) => self.binary::<u128>(|a, b| shuffle(a, b, indices.iter().copied()))?,
+            // Indices past the first operand select zero.
+            Instruction::I8x16Swizzle | Instruction::I8x16RelaxedSwizzle => self.binary::<u128>(|a, b| shuffle(a, 0, b.to_le_bytes().into_iter()))?,
+            Instruction::I8x16Splat => self.convert(|a: i32| splat(a as i8))?,
+            Instruction::I16x8Splat => self.convert(|a: i32| splat(a as i16))?,
+            Instruction::I32x4Splat => self.convert(splat::<i32>)?,
+            Instruction::I64x2Splat => self.convert(splat::<i64>)?,
+            Instruction::F32x4Splat => self.convert(splat::<f32>)?,
+            Instruction::F64x2Splat => self.convert(splat::<f64>)?,
+            Instruction::I8x16ExtractLaneS(lane) => self.convert(|a: u128| simd::lane::<i8>(a, *lane as usize) as i32)?,
+            Instruction::I8x16ExtractLaneU(lane) => self.convert(|a: u128| simd::lane::<u8>(a, *lane as usize) as i32)?,
+            Instruction::I16x8ExtractLaneS(lane) => self.convert(|a: u128| simd::lane::<i16>(a, *lane as usize) as i32)?,
+            Instruction::I16x8ExtractLaneU(lane) => self.convert(|a: u128| simd::lane::<u16>(a, *lane as usize) as i32)?,
+            Instruction::I32x4ExtractLane(lane) => self.convert(|a: u128| simd::lane::<i32>(a, *lane as usize))?,
+            Instruction::I64x2ExtractLane(lane) => self.convert(|a: u128| simd::lane::<i64>(a, *lane as usize))?,
+            Instruction::F32x4ExtractLane(lane) => self.convert(|a: u128| simd::lane::<f32>(a, *lane as usize))?,
+            Instruction::F64x2ExtractLane(lane) => self.convert(|a: u128| simd::lane::<f64>(a, *lane as usize))?,
+            Instruction::I8x16ReplaceLane(lane) => {
+                let value = self.pop::<i32>()?;
+                self.unary::<u128>(|a| replace_lane(a, *lane as usize, value as i8))?;
+            },
+            Instruction::I16x8ReplaceLane(lane) => {
+                let value = self.pop::<i32>()?;
+                self.unary::<u128>(|a| replace_lane(a, *lane as usize, value as i16))?;
+            },
+            Instruction::I32x4ReplaceLane(lane) => {
+                let value = self.pop::<i32>()?;
+                self.unary::<u128>(|a| replace_lane(a, *lane as usize, value))?;
+            },
+            Instruction::I64x2ReplaceLane(lane) => {
+                let value = self.pop::<i64>()?;
+                self.unary::<u128>(|a| replace_lane(a, *lane as usize, value))?;
+            },
+            Instruction::F32x4ReplaceLane(lane) => {
+                let value = self.pop::<f32>()?;
+                self.unary::<u128>(|a| replace_lane(a, *lane as usize, value))?;
+            },
+            Instruction::F64x2ReplaceLane(lane) => {
+                let value = self.pop::<f64>()?;
+                self.unary::<u128>(|a| replace_lane(a, *lane as usize, value))?;
+            },
+            Instruction::I8x16Eq => self.lanewise_compare::<i8>(|a, b| a == b)?,
+            Instruction::I8x16Ne => self.lanewise_compare::<i8>(|a, b| a != b)?,
+            Instruction::I8x16LtS => self.lanewise_compare::<i8>(|a, b| a < b)?,
+            Instruction::I8x16LtU => self.lanewise_compare::<u8>(|a, b| a < b)?,
+            Instruction::I8x16GtS => self.lanewise_compare::<i8>(|a, b| a > b)?,
+            Instruction::I8x16GtU => self.lanewise_compare::<u8>(|a, b| a > b)?,
+            Instruction::I8x16LeS => self.lanewise_compare::<i8>(|a, b| a <= b)?,
+            Instruction::I8x16LeU => self.lanewise_compare::<u8>(|a, b| a <= b)?,
+            Instruction::I8x16GeS => self.lanewise_compare::<i8>(|a, b| a >= b)?,
+            Instruction::I8x16GeU => self.lanewise_compare::<u8>(|a, b| a >= b)?,
+            Instruction::I16x8Eq => self.lanewise_compare::<i16>(|a, b| a == b)?,
+            Instruction::I16x8Ne => self.lanewise_compare::<i16>(|a, b| a != b)?,
+            Instruction::I16x8LtS => self.lanewise_compare::<i16>(|a, b| a < b)?,
+            Instruction::I16x8LtU => self.lanewise_compare::<u16>(|a, b| a < b)?,
+            Instruction::I16x8GtS => self.lanewise_compare::<i16>(|a, b| a > b)?,
+            Instruction::I16x8GtU => self.lanewise_compare::<u16>(|a, b| a > b)?,
+            Instruction::I16x8LeS => self.lanewise_compare::<i16>(|a, b| a <= b)?,
+            Instruction::I16x8LeU => self.lanewise_compare::<u16>(|a, b| a <= b)?,
+            Instruction::I16x8GeS => self.lanewise_compare::<i16>(|a, b| a >= b)?,
+            Instruction::I16x8GeU => self.lanewise_compare::<u16>(|a, b| a >= b)?,
+            Instruction::I32x4Eq => self.lanewise_compare::<i32>(|a, b| a == b)?,
+            Instruction::I32x4Ne => self.lanewise_compare::<i32>(|a, b| a != b)?,
+            Instruction::I32x4LtS => self.lanewise_compare::<i32>(|a, b| a < b)?,
+            Instruction::I32x4LtU => self.lanewise_compare::<u32>(|a, b| a < b)?,
+            Instruction::I32x4GtS => self.lanewise_compare::<i32>(|a, b| a > b)?,
+            Instruction::I32x4GtU => self.lanewise_compare::<u32>(|a, b| a > b)?,
+            Instruction::I32x4LeS => self.lanewise_compare::<i32>(|a, b| a <= b)?,
+            Instruction::I32x4LeU => self.lanewise_compare::<u32>(|a, b| a <= b)?,
+            Instruction::I32x4GeS => self.lanewise_compare::<i32>(|a, b| a >= b)?,
+            Instruction::I32x4GeU => self.lanewise_compare::<u32>(|a, b| a >= b)?,
+            Instruction::I64x2Eq => self.lanewise_compare::<i64>(|a, b| a == b)?,
+            Instruction::I64x2Ne => self.lanewise_compare::<i64>(|a, b| a != b)?,
+            Instruction::I64x2LtS => self.lanewise_compare::<i64>(|a, b| a < b)?,
+            Instruction::I64x2GtS => self.lanewise_compare::<i64>(|a, b| a > b)?,
+            Instruction::I64x2LeS => self.lanewise_compare::<i64>(|a, b| a <= b)?,
+            Instruction::I64x2GeS => self.lanewise_compare::<i64>(|a, b| a >= b)?,
+            Instruction::F32x4Eq => self.lanewise_compare::<f32>(|a, b| a == b)?,
+            Instruction::F32x4Ne => self.lanewise_compare::<f32>(|a, b| a != b)?,
+            Instruction::F32x4Lt => self.lanewise_compare::<f32>(|a, b| a < b)?,
+            Instruction::F32x4Gt => self.lanewise_compare::<f32>(|a, b| a > b)?,
+            Instruction::F32x4Le => self.lanewise_compare::<f32>(|a, b| a <= b)?,
+            Instruction::F32x4Ge => self.lanewise_compare::<f32>(|a, b| a >= b)?,
+            Instruction::F64x2Eq => self.lanewise_compare::<f64>(|a, b| a == b)?,
+            Instruction::F64x2Ne => self.lanewise_compare::<f64>(|a, b| a != b)?,
+            Instruction::F64x2Lt => self.lanewise_compare::<f64>(|a, b| a < b)?,
+            Instruction::F64x2Gt => self.lanewise_compare::<f64>(|a, b| a > b)?,
+            Instruction::F64x2Le => self.lanewise_compare::<f64>(|a, b| a <= b)?,
+            Instruction::F64x2Ge => self.lanewise_compare::<f64>(|a, b| a >= b)?,
+            Instruction::V128Not => self.unary::<u128>(|a| !a)?,
+            Instruction::V128And => self.binary::<u128>(|a, b| a & b)?,
+            Instruction::V128AndNot => self.binary::<u128>(|a, b| a & !b)?,
+            Instruction::V128Or => self.binary::<u128>(|a, b| a | b)?,
+            Instruction::V128Xor => self.binary::<u128>(|a, b| a ^ b)?,
+            Instruction::V128Bitselect | Instruction::I8x16RelaxedLaneselect | Instruction::I16x8RelaxedLaneselect
+            | Instruction::I32x4RelaxedLaneselect | Instruction::I64x2RelaxedLaneselect => {
+                let mask = self.pop::<u128>()?;
+                self.binary::<u128>(|a, b| a & mask | b & !mask)?;
+            },
+            Instruction::V128AnyTrue => self.convert(|a: u128| (a != 0) as i32)?,
+            Instruction::I8x16AllTrue => self.convert(all_true::<i8>)?,
+            Instruction::I16x8AllTrue => self.convert(all_true::<i16>)?,
+            Instruction::I32x4AllTrue => self.convert(all_true::<i32>)?,
+            Instruction::I64x2AllTrue => self.convert(all_true::<i64>)?,
+            Instruction::I8x16Bitmask => self.convert(bitmask::<i8>)?,
+            Instruction::I16x8Bitmask => self.convert(bitmask::<i16>)?,
+            Instruction::I32x4Bitmask => self.convert(bitmask::<i32>)?,
+            Instruction::I64x2Bitmask => self.convert(bitmask::<i64>)?,
+            Instruction::I8x16Abs => self.lanewise_unary::<i8>(i8::wrapping_abs)?,
+            Instruction::I8x16Neg => self.lanewise_unary::<i8>(i8::wrapping_neg)?,
+            Instruction::I8x16Popcnt => self.lanewise_unary::<u8>(|a| a.count_ones() as u8)?,
+            Instruction::I8x16NarrowI16x8S => self.binary::<u128>(|a, b| from_lanes(lanes::<i16>(a).chain(lanes::<i16>(b)).map(|a| a.clamp(i8::MIN as i16, i8::MAX as i16) as i8)))?,
+            Instruction::I8x16NarrowI16x8U => self.binary::<u128>(|a, b| from_lanes(lanes::<i16>(a).chain(lanes::<i16>(b)).map(|a| a.clamp(0, u8::MAX as i16) as u8)))?,
+            Instruction::I8x16Shl => self.lanewise_shift::<i8>(i8::wrapping_shl)?,
+            Instruction::I8x16ShrS => self.lanewise_shift::<i8>(i8::wrapping_shr)?,
+            Instruction::I8x16ShrU => self.lanewise_shift::<u8>(u8::wrapping_shr)?,
+            Instruction::I8x16Add => self.lanewise_binary::<i8>(i8::wrapping_add)?,
+            Instruction::I8x16AddSatS => self.lanewise_binary::<i8>(i8::saturating_add)?,
+            Instruction::I8x16AddSatU => self.lanewise_binary::<u8>(u8::saturating_add)?,
+            Instruction::I8x16Sub => self.lanewise_binary::<i8>(i8::wrapping_sub)?,
+            Instruction::I8x16SubSatS => self.lanewise_binary::<i8>(i8::saturating_sub)?,
+            Instruction::I8x16SubSatU => self.lanewise_binary::<u8>(u8::saturating_sub)?,
+            Instruction::I8x16MinS => self.lanewise_binary::<i8>(i8::min)?,
+            Instruction::I8x16MinU => self.lanewise_binary::<u8>(u8::min)?,
+            Instruction::I8x16MaxS => self.lanewise_binary::<i8>(i8::max)?,
+            Instruction::I8x16MaxU => self.lanewise_binary::<u8>(u8::max)?,
+            Instruction::I8x16AvgrU => self.lanewise_binary::<u8>(|a, b| (a as u16 + b as u16).div_ceil(2) as u8)?,
+            Instruction::I16x8ExtAddPairwiseI8x16S => self.unary::<u128>(|a| from_lanes(pairs::<i8>(a).map(|(a, b)| a as i16 + b as i16)))?,
+            Instruction::I16x8ExtAddPairwiseI8x16U => self.unary::<u128>(|a| from_lanes(pairs::<u8>(a).map(|(a, b)| a as u16 + b as u16)))?,
+            Instruction::I16x8Abs => self.lanewise_unary::<i16>(i16::wrapping_abs)?,
+            Instruction::I16x8Neg => self.lanewise_unary::<i16>(i16::wrapping_neg)?,
+            Instruction::I16x8Q15MulrSatS | Instruction::I16x8RelaxedQ15mulrS => self.lanewise_binary::<i16>(q15mulr_sat)?,
+            Instruction::I16x8NarrowI32x4S => self.binary::<u128>(|a, b| from_lanes(lanes::<i32>(a).chain(lanes::<i32>(b)).map(|a| a.clamp(i16::MIN as i32, i16::MAX as i32) as i16)))?,
+            Instruction::I16x8NarrowI32x4U => self.binary::<u128>(|a, b| from_lanes(lanes::<i32>(a).chain(lanes::<i32>(b)).map(|a| a.clamp(0, u16::MAX as i32) as u16)))?,
+            Instruction::I16x8ExtendLowI8x16S => self.unary::<u128>(|a| from_lanes(lanes::<i8>(a).take(8).map(i16::from)))?,
+            Instruction::I16x8ExtendHighI8x16S => self.unary::<u128>(|a| from_lanes(lanes::<i8>(a).skip(8).map(i16::from)))?,
+            Instruction::I16x8ExtendLowI8x16U => self.unary::<u128>(|a| from_lanes(lanes::<u8>(a).take(8).map(u16::from)))?,
+            Instruction::I16x8ExtendHighI8x16U => self.unary::<u128>(|a| from_lanes(lanes::<u8>(a).skip(8).map(u16::from)))?,
+            Instruction::I16x8Shl => self.lanewise_shift::<i16>(i16::wrapping_shl)?,
+            Instruction::I16x8ShrS => self.lanewise_shift::<i16>(i16::wrapping_shr)?,
+            Instruction::I16x8ShrU => self.lanewise_shift::<u16>(u16::wrapping_shr)?,
+            Instruction::I16x8Add => self.lanewise_binary::<i16>(i16::wrapping_add)?,
+            Instruction::I16x8AddSatS => self.lanewise_binary::<i16>(i16::saturating_add)?,
+            Instruction::I16x8AddSatU => self.lanewise_binary::<u16>(u16::saturating_add)?,
+            Instruction::I16x8Sub => self.lanewise_binary::<i16>(i16::wrapping_sub)?,
+            Instruction::I16x8SubSatS => self.lanewise_binary::<i16>(i16::saturating_sub)?,
+            Instruction::I16x8SubSatU => self.lanewise_binary::<u16>(u16::saturating_sub)?,
+            Instruction::I16x8Mul => self.lanewise_binary::<i16>(i16::wrapping_mul)?,
+            Instruction::I16x8MinS => self.lanewise_binary::<i16>(i16::min)?,
+            Instruction::I16x8MinU => self.lanewise_binary::<u16>(u16::min)?,
+            Instruction::I16x8MaxS => self.lanewise_binary::<i16>(i16::max)?,
+            Instruction::I16x8MaxU => self.lanewise_binary::<u16>(u16::max)?,
+            Instruction::I16x8AvgrU => self.lanewise_binary::<u16>(|a, b| (a as u32 + b as u32).div_ceil(2) as u16)?,
+            Instruction::I16x8ExtMulLowI8x16S => self.binary::<u128>(|a, b| from_lanes(lanes::<i8>(a).zip(lanes::<i8>(b)).take(8).map(|(a, b)| a as i16 * b as i16)))?,
+            Instruction::I16x8ExtMulHighI8x16S => self.binary::<u128>(|a, b| from_lanes(lanes::<i8>(a).zip(lanes::<i8>(b)).skip(8).map(|(a, b)| a as i16 * b as i16)))?,
+            Instruction::I16x8ExtMulLowI8x16U => self.binary::<u128>(|a, b| from_lanes(lanes::<u8>(a).zip(lanes::<u8>(b)).take(8).map(|(a, b)| a as u16 * b as u16)))?,
+            Instruction::I16x8ExtMulHighI8x16U => self.binary::<u128>(|a, b| from_lanes(lanes::<u8>(a).zip(lanes::<u8>(b)).skip(8).map(|(a, b)| a as u16 * b as u16)))?,
+            Instruction::I32x4ExtAddPairwiseI16x8S => self.unary::<u128>(|a| from_lanes(pairs::<i16>(a).map(|(a, b)| a as i32 + b as i32)))?,
+            Instruction::I32x4ExtAddPairwiseI16x8U => self.unary::<u128>(|a| from_lanes(pairs::<u16>(a).map(|(a, b)| a as u32 + b as u32)))?,
+            Instruction::I32x4Abs => self.lanewise_unary::<i32>(i32::wrapping_abs)?,
+            Instruction::I32x4Neg => self.lanewise_unary::<i32>(i32::wrapping_neg)?,
+            Instruction::I32x4ExtendLowI16x8S => self.unary::<u128>(|a| from_lanes(lanes::<i16>(a).take(4).map(i32::from)))?,
+            Instruction::I32x4ExtendHighI16x8S => self.unary::<u128>(|a| from_lanes(lanes::<i16>(a).skip(4).map(i32::from)))?,
+            Instruction::I32x4ExtendLowI16x8U => self.unary::<u128>(|a| from_lanes(lanes::<u16>(a).take(4).map(u32::from)))?,
+            Instruction::I32x4ExtendHighI16x8U => self.unary::<u128>(|a| from_lanes(lanes::<u16>(a).skip(4).map(u32::from)))?,
+            Instruction::I32x4Shl => self.lanewise_shift::<i32>(i32::wrapping_shl)?,
+            Instruction::I32x4ShrS => self.lanewise_shift::<i32>(i32::wrapping_shr)?,
+            Instruction::I32x4ShrU => self.lanewise_shift::<u32>(u32::wrapping_shr)?,
+            Instruction::I32x4Add => self.lanewise_binary::<i32>(i32::wrapping_add)?,
+            Instruction::I32x4Sub => self.lanewise_binary::<i32>(i32::wrapping_sub)?,
+            Instruction::I32x4Mul => self.lanewise_binary::<i32>(i32::wrapping_mul)?,
+            Instruction::I32x4MinS => self.lanewise_binary::<i32>(i32::min)?,
+            Instruction::I32x4MinU => self.lanewise_binary::<u32>(u32::min)?,
+            Instruction::I32x4MaxS => self.lanewise_binary::<i32>(i32::max)?,
+            Instruction::I32x4MaxU => self.lanewise_binary::<u32>(u32::max)?,
+            Instruction::I32x4DotI16x8S => self.binary::<u128>(|a, b| from_lanes(pairs::<i16>(a).zip(pairs::<i16>(b)).map(|((a1, a2), (b1, b2))| (a1 as i32 * b1 as i32).wrapping_add(a2 as i32 * b2 as i32))))?,
+            Instruction::I32x4ExtMulLowI16x8S => self.binary::<u128>(|a, b| from_lanes(lanes::<i16>(a).zip(lanes::<i16>(b)).take(4).map(|(a, b)| a as i32 * b as i32)))?,
+            Instruction::I32x4ExtMulHighI16x8S => self.binary::<u128>(|a, b| from_lanes(lanes::<i16>(a).zip(lanes::<i16>(b)).skip(4).map(|(a, b)| a as i32 * b as i32)))?,
+            Instruction::I32x4ExtMulLowI16x8U => self.binary::<u128>(|a, b| from_lanes(lanes::<u16>(a).zip(lanes::<u16>(b)).take(4).map(|(a, b)| a as u32 * b as u32)))?,
+            Instruction::I32x4ExtMulHighI16x8U => self.binary::<u128>(|a, b| from_lanes(lanes::<u16>(a).zip(lanes::<u16>(b)).skip(4).map(|(a, b)| a as u32 * b as u32)))?,
+            Instruction::I64x2Abs => self.lanewise_unary::<i64>(i64::wrapping_abs)?,
+            Instruction::I64x2Neg => self.lanewise_unary::<i64>(i64::wrapping_neg)?,
+            Instruction::I64x2ExtendLowI32x4S => self.unary::<u128>(|a| from_lanes(lanes::<i32>(a).take(2).map(i64::from)))?,
+            Instruction::I64x2ExtendHighI32x4S => self.unary::<u128>(|a| from_lanes(lanes::<i32>(a).skip(2).map(i64::from)))?,
+            Instruction::I64x2ExtendLowI32x4U => self.unary::<u128>(|a| from_lanes(lanes::<u32>(a).take(2).map(u64::from)))?,
+            Instruction::I64x2ExtendHighI32x4U => self.unary::<u128>(|a| from_lanes(lanes::<u32>(a).skip(2).map(u64::from)))?,
+            Instruction::I64x2Shl => self.lanewise_shift::<i64>(i64::wrapping_shl)?,
+            Instruction::I64x2ShrS => self.lanewise_shift::<i64>(i64::wrapping_shr)?,
+            Instruction::I64x2ShrU => self.lanewise_shift::<u64>(u64::wrapping_shr)?,
+            Instruction::I64x2Add => self.lanewise_binary::<i64>(i64::wrapping_add)?,
+            Instruction::I64x2Sub => self.lanewise_binary::<i64>(i64::wrapping_sub)?,
+            Instruction::I64x2Mul => self.lanewise_binary::<i64>(i64::wrapping_mul)?,
+            Instruction::I64x2ExtMulLowI32x4S => self.binary::<u128>(|a, b| from_lanes(lanes::<i32>(a).zip(lanes::<i32>(b)).take(2).map(|(a, b)| a as i64 * b as i64)))?,
+            Instruction::I64x2ExtMulHighI32x4S => self.binary::<u128>(|a, b| from_lanes(lanes::<i32>(a).zip(lanes::<i32>(b)).skip(2).map(|(a, b)| a as i64 * b as i64)))?,
+            Instruction::I64x2ExtMulLowI32x4U => self.binary::<u128>(|a, b| from_lanes(lanes::<u32>(a).zip(lanes::<u32>(b)).take(2).map(|(a, b)| a as u64 * b as u64)))?,
+            Instruction::I64x2ExtMulHighI32x4U => self.binary::<u128>(|a, b| from_lanes(lanes::<u32>(a).zip(lanes::<u32>(b)).skip(2).map(|(a, b)| a as u64 * b as u64)))?,
+            Instruction::F32x4Ceil => self.lanewise_unary::<f32>(|a| canonicalize_f32(a.ceil()))?,
+            Instruction::F32x4Floor => self.lanewise_unary::<f32>(|a| canonicalize_f32(a.floor()))?,
+            Instruction::F32x4Trunc => self.lanewise_unary::<f32>(|a| canonicalize_f32(a.trunc()))?,
+            Instruction::F32x4Nearest => self.lanewise_unary::<f32>(|a| canonicalize_f32(a.round_ties_even()))?,
+            Instruction::F32x4Abs => self.lanewise_unary::<f32>(f32::abs)?,
+            Instruction::F32x4Neg => self.lanewise_unary::<f32>(|a| -a)?,
+            Instruction::F32x4Sqrt => self.lanewise_unary::<f32>(|a| canonicalize_f32(a.sqrt()))?,
+            Instruction::F32x4Add => self.lanewise_binary::<f32>(|a, b| canonicalize_f32(a + b))?,
+            Instruction::F32x4Sub => self.lanewise_binary::<f32>(|a, b| canonicalize_f32(a - b))?,
+            Instruction::F32x4Mul => self.lanewise_binary::<f32>(|a, b| canonicalize_f32(a * b))?,
+            Instruction::F32x4Div => self.lanewise_binary::<f32>(|a, b| canonicalize_f32(a / b))?,
+            Instruction::F32x4Min => self.lanewise_binary::<f32>(|a, b| canonicalize_f32(f32_min(a, b)))?,
+            Instruction::F32x4Max => self.lanewise_binary::<f32>(|a, b| canonicalize_f32(f32_max(a, b)))?,
+            Instruction::F32x4PMin => self.lanewise_binary::<f32>(|a, b| if b < a { b } else { a })?,
+            Instruction::F32x4PMax => self.lanewise_binary::<f32>(|a, b| if a < b { b } else { a })?,
+            Instruction::F64x2Ceil => self.lanewise_unary::<f64>(|a| canonicalize_f64(a.ceil()))?,
+            Instruction::F64x2Floor => self.lanewise_unary::<f64>(|a| canonicalize_f64(a.floor()))?,
+            Instruction::F64x2Trunc => self.lanewise_unary::<f64>(|a| canonicalize_f64(a.trunc()))?,
+            Instruction::F64x2Nearest => self.lanewise_unary::<f64>(|a| canonicalize_f64(a.round_ties_even()))?,
+            Instruction::F64x2Abs => self.lanewise_unary::<f64>(f64::abs)?,
+            Instruction::F64x2Neg => self.lanewise_unary::<f64>(|a| -a)?,
+            Instruction::F64x2Sqrt => self.lanewise_unary::<f64>(|a| canonicalize_f64(a.sqrt()))?,
+            Instruction::F64x2Add => self.lanewise_binary::<f64>(|a, b| canonicalize_f64(a + b))?,
+            Instruction::F64x2Sub => self.lanewise_binary::<f64>(|a, b| canonicalize_f64(a - b))?,
+            Instruction::F64x2Mul => self.lanewise_binary::<f64>(|a, b| canonicalize_f64(a * b))?,
+            Instruction::F64x2Div => self.lanewise_binary::<f64>(|a, b| canonicalize_f64(a / b))?,
+            Instruction::F64x2Min => self.lanewise_binary::<f64>(|a, b| canonicalize_f64(f64_min(a, b)))?,
+            Instruction::F64x2Max => self.lanewise_binary::<f64>(|a, b| canonicalize_f64(f64_max(a, b)))?,
+            Instruction::F64x2PMin => self.lanewise_binary::<f64>(|a, b| if b < a { b } else { a })?,
+            Instruction::F64x2PMax => self.lanewise_binary::<f64>(|a, b| if a < b { b } else { a })?,
+            Instruction::I32x4TruncSatF32x4S | Instruction::I32x4RelaxedTruncF32x4S => self.unary::<u128>(|a| from_lanes(lanes::<f32>(a).map(|a| a as i32)))?,
+            Instruction::I32x4TruncSatF32x4U | Instruction::I32x4RelaxedTruncF32x4U => self.unary::<u128>(|a| from_lanes(lanes::<f32>(a).map(|a| a as u32)))?,
+            Instruction::F32x4ConvertI32x4S => self.unary::<u128>(|a| from_lanes(lanes::<i32>(a).map(|a| a as f32)))?,
+            Instruction::F32x4ConvertI32x4U => self.unary::<u128>(|a| from_lanes(lanes::<u32>(a).map(|a| a as f32)))?,
+            Instruction::I32x4TruncSatF64x2SZero | Instruction::I32x4RelaxedTruncF64x2SZero => self.unary::<u128>(|a| from_lanes(lanes::<f64>(a).map(|a| a as i32)))?,
+            Instruction::I32x4TruncSatF64x2UZero | Instruction::I32x4RelaxedTruncF64x2UZero => self.unary::<u128>(|a| from_lanes(lanes::<f64>(a).map(|a| a as u32)))?,
+            Instruction::F64x2ConvertLowI32x4S => self.unary::<u128>(|a| from_lanes(lanes::<i32>(a).take(2).map(f64::from)))?,
+            Instruction::F64x2ConvertLowI32x4U => self.unary::<u128>(|a| from_lanes(lanes::<u32>(a).take(2).map(f64::from)))?,
+            Instruction::F32x4DemoteF64x2Zero => self.unary::<u128>(|a| from_lanes(lanes::<f64>(a).map(|a| canonicalize_f32(a as f32))))?,
+            Instruction::F64x2PromoteLowF32x4 => self.unary::<u128>(|a| from_lanes(lanes::<f32>(a).take(2).map(|a| canonicalize_f64(a as f64))))?,
+            // The relaxed operations not shared with the ones above. Unless
+            // pinned to their deterministic behavior, multiply-adds round twice
+            // and min/max return either operand for NaNs and zeros of both signs.
+            Instruction::F32x4RelaxedMadd if self.config.relaxed_simd_deterministic => self.lanewise_ternary::<f32>(|a, b, c| canonicalize_f32(a.mul_add(b, c)))?,
+            Instruction::F32x4RelaxedMadd => self.lanewise_ternary::<f32>(|a, b, c| canonicalize_f32(a * b + c))?,
+            Instruction::F32x4RelaxedNmadd if self.config.relaxed_simd_deterministic => self.lanewise_ternary::<f32>(|a, b, c| canonicalize_f32((-a).mul_add(b, c)))?,
+            Instruction::F32x4RelaxedNmadd => self.lanewise_ternary::<f32>(|a, b, c| canonicalize_f32(-a * b + c))?,
+            Instruction::F64x2RelaxedMadd if self.config.relaxed_simd_deterministic => self.lanewise_ternary::<f64>(|a, b, c| canonicalize_f64(a.mul_add(b, c)))?,
+            Instruction::F64x2RelaxedMadd => self.lanewise_ternary::<f64>(|a, b, c| canonicalize_f64(a * b + c))?,
+            Instruction::F64x2RelaxedNmadd if self.config.relaxed_simd_deterministic => self.lanewise_ternary::<f64>(|a, b, c| canonicalize_f64((-a).mul_add(b, c)))?,
+            Instruction::F64x2RelaxedNmadd => self.lanewise_ternary::<f64>(|a, b, c| canonicalize_f64(-a * b + c))?,
+            Instruction::F32x4RelaxedMin if self.config.relaxed_simd_deterministic => self.lanewise_binary::<f32>(|a, b| canonicalize_f32(f32_min(a, b)))?,
+            Instruction::F32x4RelaxedMin => self.lanewise_binary::<f32>(|a, b| if a < b { a } else { b })?,
+            Instruction::F32x4RelaxedMax if self.config.relaxed_simd_deterministic => self.lanewise_binary::<f32>(|a, b| canonicalize_f32(f32_max(a, b)))?,
+            Instruction::F32x4RelaxedMax => self.lanewise_binary::<f32>(|a, b| if a > b { a } else { b })?,
+            Instruction::F64x2RelaxedMin if self.config.relaxed_simd_deterministic => self.lanewise_binary::<f64>(|a, b| canonicalize_f64(f64_min(a, b)))?,
+            Instruction::F64x2RelaxedMin => self.lanewise_binary::<f64>(|a, b| if a < b { a } else { b })?,
+            Instruction::F64x2RelaxedMax if self.config.relaxed_simd_deterministic => self.lanewise_binary::<f64>(|a, b| canonicalize_f64(f64_max(a, b)))?,
+            Instruction::F64x2RelaxedMax => self.lanewise_binary::<f64>(|a, b| if a > b { a } else { b })?,
+            // Both operands are taken as signed, which is the deterministic
+            // behavior and no slower than the alternatives. Pairs of products
+            // wrap around as 16-bit sums before the 32-bit dot adds them up.
+            Instruction::I16x8RelaxedDotI8x16I7x16S => self.binary::<u128>(relaxed_dot)?,
+            Instruction::I32x4RelaxedDotI8x16I7x16AddS => {
+                let c = self.pop::<u128>()?;
+                self.binary::<u128>(|a, b| from_lanes(pairs::<i16>(relaxed_dot(a, b)).zip(lanes::<i32>(c)).map(|((a1, a2), c)| (a1 as i32 + a2 as i32).wrapping_add(c))))?;
+            },
+            Instruction::Call(index) => return Ok(Control::Call(*index)),
+            Instruction::CallIndirect { type_index, table } => {
+                let index = self.indirect_callee(bytecode, *type_index, *table)?;
+                return Ok(Control::Call(index));
+            }
+            Instruction::ReturnCall(index) => return self.tail_call(bytecode, frame, *index),
+            Instruction::ReturnCallIndirect { type_index, table } => {
+                let index = self.indirect_callee(bytecode, *type_index, *table)?;
+                return self.tail_call(bytecode, frame, index);
             }
             Instruction::LocalGet(index) => {
-                self.stack.push(locals[*index as usize].value);
+                self.stack.push(frame.locals[*index as usize].value);
             },
             Instruction::LocalSet(index) => {
-                locals[*index as usize].value = self.stack.pop().unwrap();
+                frame.locals[*index as usize].value = self.pop()?;
+            },
+            Instruction::LocalTee(index) => {
+                let value = *self.stack.last().ok_or(Trap::new(TrapKind::StackUnderflow))?;
+                frame.locals[*index as usize].value = value;
+            },
+            Instruction::Unreachable => {
+                return Err(Trap::new(TrapKind::Unreachable).into());
+            },
+            Instruction::Nop => {},
+            Instruction::Drop => {
+                self.pop::<i128>()?;
+            },
+            Instruction::Select => {
+                let condition = self.pop::<i32>()?;
+                let b = self.pop::<i128>()?;
+                let a = self.pop::<i128>()?;
+                self.push(if condition != 0 { a } else { b });
+            },
+            Instruction::GlobalGet(index) => {
+                let value = *self.globals.get(*index as usize).ok_or_else(|| anyhow::anyhow!("Global not found"))?;
+                self.stack.push(value);
+            },
+            Instruction::GlobalSet(index) => {
+                let value = self.pop()?;
+                *self.globals.get_mut(*index as usize).ok_or_else(|| anyhow::anyhow!("Global not found"))? = value;
+            },
+            Instruction::I32Load(memarg) => {
+                let value = i32::from_le_bytes(self.load(memarg)?);
+                self.stack.push(value as i128);
+            },
+            Instruction::I64Load(memarg) => {
+                let value = i64::from_le_bytes(self.load(memarg)?);
+                self.stack.push(value as i128);
+            },
+            Instruction::F32Load(memarg) => {
+                let bits = u32::from_le_bytes(self.load(memarg)?);
+                self.stack.push(bits as i128);
+            },
+            Instruction::F64Load(memarg) => {
+                let bits = u64::from_le_bytes(self.load(memarg)?);
+                self.stack.push(bits as i128);
+            },
+            Instruction::I32Load8S(memarg) => {
+                let value = i8::from_le_bytes(self.load(memarg)?);
+                self.stack.push(value as i32 as i128);
+            },
+            Instruction::I32Load8U(memarg) => {
+                let value = u8::from_le_bytes(self.load(memarg)?);
+                self.stack.push(value as i32 as i128);
+            },
+            Instruction::I32Load16S(memarg) => {
+                let value = i16::from_le_bytes(self.load(memarg)?);
+                self.stack.push(value as i32 as i128);
+            },
+            Instruction::I32Load16U(memarg) => {
+                let value = u16::from_le_bytes(self.load(memarg)?);
+                self.stack.push(value as i32 as i128);
+            },
+            Instruction::I64Load8S(memarg) => {
+                let value = i8::from_le_bytes(self.load(memarg)?);
+                self.stack.push(value as i128);
+            },
+            Instruction::I64Load8U(memarg) => {
+                let value = u8::from_le_bytes(self.load(memarg)?);
+                self.stack.push(value as i128);
+            },
+            Instruction::I64Load16S(memarg) => {
+                let value = i16::from_le_bytes(self.load(memarg)?);
+                self.stack.push(value as i128);
+            },
+            Instruction::I64Load16U(memarg) => {
+                let value = u16::from_le_bytes(self.load(memarg)?);
+                self.stack.push(value as i128);
+            },
+            Instruction::I64Load32S(memarg) => {
+                let value = i32::from_le_bytes(self.load(memarg)?);
+                self.stack.push(value as i128);
+            },
+            Instruction::I64Load32U(memarg) => {
+                let value = u32::from_le_bytes(self.load(memarg)?);
+                self.stack.push(value as i128);
+            },
+            Instruction::I32Store(memarg) | Instruction::F32Store(memarg) => {
+                let value = self.pop::<u32>()?;
+                self.store(memarg, &value.to_le_bytes())?;
+            },
+            Instruction::I64Store(memarg) | Instruction::F64Store(memarg) => {
+                let value = self.pop::<i64>()?;
+                self.store(memarg, &value.to_le_bytes())?;
+            },
+            Instruction::I32Store8(memarg) | Instruction::I64Store8(memarg) => {
+                let value = self.pop::<u32>()? as u8;
+                self.store(memarg, &value.to_le_bytes())?;
+            },
+            Instruction::I32Store16(memarg) | Instruction::I64Store16(memarg) => {
+                let value = self.pop::<u32>()? as u16;
+                self.store(memarg, &value.to_le_bytes())?;
+            },
+            Instruction::I64Store32(memarg) => {
+                let value = self.pop::<u32>()?;
+                self.store(memarg, &value.to_le_bytes())?;
+            },
+            Instruction::MemorySize(index) => {
+                let size = self.memory(*index)?.size();
+                self.stack.push(size as i32 as i128);
+            },
+            Instruction::MemoryGrow(index) => {
+                let delta = self.pop::<u32>()?;
+                let result = self.memory(*index)?.grow(delta).map_or(-1, |size| size as i32);
+                self.stack.push(result as i128);
+            },
+            Instruction::MemoryInit { data, memory } => self.memory_init(bytecode, *data, *memory)?,
+            Instruction::DataDrop(data) => {
+                let dropped = self.dropped_data.get_mut(*data as usize).ok_or_else(|| anyhow::anyhow!("Data segment not found"))?;
+                *dropped = true;
+            },
+            Instruction::MemoryCopy { destination, source } => self.memory_copy(*destination, *source)?,
+            Instruction::MemoryFill(index) => {
+                let len = self.pop::<u32>()? as usize;
+                let value = self.pop::<u32>()? as u8;
+                let address = self.pop::<u32>()? as u64;
+                self.memory(*index)?.fill(address, value, len)?;
+            },
+            Instruction::TableGet(index) => {
+                let element = self.pop::<u32>()?;
+                let value = self.table(*index)?.get(element)?;
+                self.push(value);
+            },
+            Instruction::TableSet(index) => {
+                let value = self.pop::<Option<u32>>()?;
+                let element = self.pop::<u32>()?;
+                self.table(*index)?.set(element, value)?;
+            },
+            Instruction::TableSize(index) => {
+                let size = self.table(*index)?.size();
+                self.push(size);
+            },
+            Instruction::TableGrow(index) => {
+                let delta = self.pop::<u32>()?;
+                let init = self.pop::<Option<u32>>()?;
+                let result = self.table(*index)?.grow(delta, init).map_or(-1, |size| size as i32);
+                self.push(result);
+            },
+            Instruction::TableFill(index) => {
+                let len = self.pop::<u32>()?;
+                let value = self.pop::<Option<u32>>()?;
+                let element = self.pop::<u32>()?;
+                self.table(*index)?.fill(element, value, len)?;
+            },
+            Instruction::TableCopy { destination, source } => self.table_copy(*destination, *source)?,
+            Instruction::TableInit { element, table } => self.table_init(*element, *table)?,
+            Instruction::ElemDrop(element) => {
+                let segment = self.element_segments.get_mut(*element as usize).ok_or_else(|| anyhow::anyhow!("Element segment not found"))?;
+                *segment = Vec::new();
+            },
+            Instruction::RefNull => self.push(None::<u32>),
+            Instruction::RefIsNull => {
+                let reference = self.pop::<Option<u32>>()?;
+                self.push(reference.is_none() as i32);
+            },
+            Instruction::RefFunc(index) => self.push(Some(*index)),
+            // A block's parameters are already on the stack, so they belong to
+            // the block rather than sitting below its label.
+            Instruction::Block { params, arity, end } => {
+                let height = self.label_height(*params)?;
+                frame.labels.push(Label { arity: *arity as usize, height, target: *end as usize, try_table: None });
+            },
+            Instruction::TryTable { params, arity, end, .. } => {
+                let height = self.label_height(*params)?;
+                frame.labels.push(Label { arity: *arity as usize, height, target: *end as usize, try_table: Some(frame.pc - 1) });
+            },
+            Instruction::Throw(index) => {
+                let tag = bytecode.tags.get(*index as usize).ok_or_else(|| anyhow::anyhow!("Tag not found"))?;
+                let values_start = self.stack.len().checked_sub(tag.params.len()).ok_or(Trap::new(TrapKind::StackUnderflow))?;
+                let values = self.stack.drain(values_start..).zip(tag.params.iter()).map(|(value, val_type)| Value { val_type: *val_type, value }).collect();
+                return Err(Exception::new(tag.tag, values).into());
+            },
+            Instruction::ThrowRef => {
+                let handle = self.pop::<Option<u32>>()?.ok_or(Trap::new(TrapKind::NullReference))?;
+                let exception = self.exceptions.get(handle).ok_or(Trap::new(TrapKind::DanglingReference))?;
+                return Err(exception.clone().into());
+            },
+            Instruction::Loop { arity } => {
+                let height = self.label_height(*arity)?;
+                frame.labels.push(Label { arity: *arity as usize, height, target: frame.pc, try_table: None });
+            },
+            Instruction::If { params, arity, else_, end } => {
+                let condition = self.pop::<i32>()?;
+                let height = self.label_height(*params)?;
+                frame.labels.push(Label { arity: *arity as usize, height, target: *end as usize, try_table: None });
+                if condition == 0 {
+                    frame.pc = *else_ as usize;
+                }
+            },
+            Instruction::Else { end } => {
+                frame.pc = *end as usize;
+            },
+            Instruction::Br(depth) => {
+                frame.pc = self.branch(frame, *depth);
+            },
+            Instruction::BrIf(depth) => {
+                let condition = self.pop::<i32>()?;
+                if condition != 0 {
+                    frame.pc = self.branch(frame, *depth);
+                }
+            },
+            Instruction::BrTable { targets, default } => {
+                let index = self.pop::<u32>()?;
+                let depth = targets.get(index as usize).unwrap_or(default);
+                frame.pc = self.branch(frame, *depth);
+            },
+            Instruction::End => {
+                frame.labels.pop();
+            },
+            Instruction::Return => {
+                let depth = frame.labels.len() as u32 - 1;
+                frame.pc = self.branch(frame, depth);
             },
-            Instruction::GlobalGet(_) => todo!(),
-            Instruction::GlobalSet(_) => todo!(),
-            Instruction::End => {},    
-            Instruction::Return => todo!(),
         }
-    Ok(())
+    Ok(Control::Continue)
     }
 
 }
@@ -0,0 +1,79 @@
+mod common;
+
+use common::Instance;
+use weloce::{TrapKind, Value};
+
+const MEMORY: &str = r#"(module
+    (memory 1)
+    (data (i32.const 0) "\00\01\02\03\04\05\06\07")
+    (data $passive "\0a\0b\0c\0d")
+    (func (export "copy") (param i32 i32 i32) (memory.copy (local.get 0) (local.get 1) (local.get 2)))
+    (func (export "fill") (param i32 i32 i32) (memory.fill (local.get 0) (local.get 1) (local.get 2)))
+    (func (export "init") (param i32 i32 i32) (memory.init $passive (local.get 0) (local.get 1) (local.get 2)))
+    (func (export "drop") (data.drop $passive))
+    (func (export "load") (result i64) (i64.load (i32.const 0))))"#;
+
+// The first eight bytes of memory.
+fn bytes(memory: &mut Instance) -> [u8; 8] {
+    memory.call_i64("load", &[]).to_le_bytes()
+}
+
+fn args(destination: i32, source: i32, len: i32) -> [Value; 3] {
+    [Value::i32(destination), Value::i32(source), Value::i32(len)]
+}
+
+#[test]
+fn copy_handles_overlap_in_both_directions() {
+    let mut memory = Instance::new(MEMORY);
+    memory.try_call("copy", &args(2, 0, 4)).unwrap();
+    assert_eq!(bytes(&mut memory), [0, 1, 0, 1, 2, 3, 6, 7]);
+
+    let mut memory = Instance::new(MEMORY);
+    memory.try_call("copy", &args(0, 2, 4)).unwrap();
+    assert_eq!(bytes(&mut memory), [2, 3, 4, 5, 4, 5, 6, 7]);
+}
+
+#[test]
+fn copy_out_of_bounds_traps() {
+    let mut memory = Instance::new(MEMORY);
+    assert_eq!(memory.trap("copy", &args(65530, 0, 8)), TrapKind::MemoryOutOfBounds);
+    assert_eq!(memory.trap("copy", &args(0, 65530, 8)), TrapKind::MemoryOutOfBounds);
+    memory.try_call("copy", &args(65536, 0, 0)).unwrap();
+}
+
+#[test]
+fn fill_sets_a_range_to_one_byte() {
+    let mut memory = Instance::new(MEMORY);
+    memory.try_call("fill", &args(1, 0x1aa, 3)).unwrap();
+    assert_eq!(bytes(&mut memory), [0, 0xaa, 0xaa, 0xaa, 4, 5, 6, 7]);
+    assert_eq!(memory.trap("fill", &args(65535, 0, 2)), TrapKind::MemoryOutOfBounds);
+    memory.try_call("fill", &args(65536, 0, 0)).unwrap();
+}
+
+#[test]
+fn init_copies_part_of_a_passive_segment() {
+    let mut memory = Instance::new(MEMORY);
+    memory.try_call("init", &args(2, 1, 2)).unwrap();
+    assert_eq!(bytes(&mut memory), [0, 1, 0x0b, 0x0c, 4, 5, 6, 7]);
+    memory.try_call("init", &args(6, 2, 2)).unwrap();
+    assert_eq!(bytes(&mut memory), [0, 1, 0x0b, 0x0c, 4, 5, 0x0c, 0x0d]);
+}
+
+#[test]
+fn init_past_the_segment_end_traps() {
+    let mut memory = Instance::new(MEMORY);
+    assert_eq!(memory.trap("init", &args(0, 3, 2)), TrapKind::MemoryOutOfBounds);
+    assert_eq!(memory.trap("init", &args(0, 5, 0)), TrapKind::MemoryOutOfBounds);
+    assert_eq!(memory.trap("init", &args(65535, 0, 2)), TrapKind::MemoryOutOfBounds);
+    memory.try_call("init", &args(0, 4, 0)).unwrap();
+    assert_eq!(bytes(&mut memory), [0, 1, 2, 3, 4, 5, 6, 7]);
+}
+
+#[test]
+fn init_after_drop_traps() {
+    let mut memory = Instance::new(MEMORY);
+    memory.try_call("drop", &[]).unwrap();
+    assert_eq!(memory.trap("init", &args(0, 0, 1)), TrapKind::MemoryOutOfBounds);
+    memory.try_call("init", &args(0, 0, 0)).unwrap();
+    memory.try_call("drop", &[]).unwrap();
+}
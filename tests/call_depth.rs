@@ -0,0 +1,39 @@
+mod common;
+
+use common::{call, instantiate, instantiate_with_config};
+use weloce::{Config, Imports, Trap, TrapKind, Value};
+
+// `depth(n)` recurses until `n` is zero, so it runs in `n + 1` frames.
+const RECURSION: &str = r#"(module
+    (func $forever (export "forever") (call $forever))
+    (func $depth (export "depth") (param i32) (result i32)
+        (if (result i32) (i32.eqz (local.get 0))
+            (then (i32.const 0))
+            (else (i32.add (call $depth (i32.sub (local.get 0) (i32.const 1))) (i32.const 1))))))"#;
+
+#[test]
+fn unbounded_recursion_exhausts_the_stack() {
+    let mut imports = Imports::new();
+    let (bytecode, mut vm) = instantiate(RECURSION, &mut imports);
+    let error = vm.call(&bytecode, "forever", &[], &mut imports).unwrap_err();
+    assert_eq!(error.downcast::<Trap>().unwrap().kind(), TrapKind::StackExhausted);
+}
+
+#[test]
+fn max_call_depth_is_exact() {
+    let mut imports = Imports::new();
+    let (bytecode, mut vm) = instantiate_with_config(RECURSION, &mut imports, Config::new().max_call_depth(10));
+    assert_eq!(call(&mut vm, &bytecode, "depth", &[Value::i32(9)], &mut imports).as_i32(), Some(9));
+    let error = vm.call(&bytecode, "depth", &[Value::i32(10)], &mut imports).unwrap_err();
+    assert_eq!(error.downcast::<Trap>().unwrap().kind(), TrapKind::StackExhausted);
+}
+
+#[test]
+fn vm_is_usable_after_exhausting_the_stack() {
+    let mut imports = Imports::new();
+    let (bytecode, mut vm) = instantiate_with_config(RECURSION, &mut imports, Config::new().max_call_depth(1000));
+    for _ in 0..3 {
+        assert!(vm.call(&bytecode, "forever", &[], &mut imports).is_err());
+        assert_eq!(call(&mut vm, &bytecode, "depth", &[Value::i32(999)], &mut imports).as_i32(), Some(999));
+    }
+}
@@ -0,0 +1,35 @@
+mod common;
+
+use common::Instance;
+use weloce::{TrapKind, Value};
+
+const DISPATCH: &str = r#"(module
+    (type $binary (func (param i32 i32) (result i32)))
+    (table 4 funcref)
+    (elem (i32.const 0) func $add $sub $negate)
+    (func $add (param i32 i32) (result i32) (i32.add (local.get 0) (local.get 1)))
+    (func $sub (param i32 i32) (result i32) (i32.sub (local.get 0) (local.get 1)))
+    (func $negate (param i32) (result i32) (i32.sub (i32.const 0) (local.get 0)))
+    (func (export "dispatch") (param i32 i32 i32) (result i32)
+        (call_indirect (type $binary) (local.get 1) (local.get 2) (local.get 0))))"#;
+
+// Calls the entry at `index` with 7 and 3.
+fn args(index: i32) -> [Value; 3] {
+    [Value::i32(index), Value::i32(7), Value::i32(3)]
+}
+
+#[test]
+fn calls_the_function_at_the_index() {
+    let mut instance = Instance::new(DISPATCH);
+    assert_eq!(instance.call_i32("dispatch", &args(0)), 10);
+    assert_eq!(instance.call_i32("dispatch", &args(1)), 4);
+}
+
+#[test]
+fn bad_entries_trap() {
+    let mut instance = Instance::new(DISPATCH);
+    assert_eq!(instance.trap("dispatch", &args(2)), TrapKind::IndirectCallTypeMismatch);
+    assert_eq!(instance.trap("dispatch", &args(3)), TrapKind::IndirectCallToNull);
+    assert_eq!(instance.trap("dispatch", &args(4)), TrapKind::TableOutOfBounds);
+    assert_eq!(instance.trap("dispatch", &args(-1)), TrapKind::TableOutOfBounds);
+}
@@ -0,0 +1,84 @@
+// Helpers shared by the integration tests. Not every test file uses all of them.
+#![allow(dead_code)]
+
+use weloce::{compile_wasm, Bytecode, Config, Imports, Return, Trap, TrapKind, Value, Vm};
+
+pub fn instantiate(wat: &str, imports: &mut Imports) -> (Bytecode, Vm) {
+    instantiate_with_config(wat, imports, Config::new())
+}
+
+pub fn instantiate_with_config(wat: &str, imports: &mut Imports, config: Config) -> (Bytecode, Vm) {
+    let wasm = wat::parse_str(wat).unwrap();
+    let bytecode = compile_wasm(&wasm, imports).unwrap();
+    let mut vm = Vm::with_config(config);
+    vm.instantiate(&bytecode, imports).unwrap();
+    (bytecode, vm)
+}
+
+pub fn call(vm: &mut Vm, bytecode: &Bytecode, name: &str, args: &[Value], imports: &mut Imports) -> Value {
+    match vm.call(bytecode, name, args, imports).unwrap() {
+        Return::Single(value) => value,
+        result => panic!("Expected a single value, got {:?}", result),
+    }
+}
+
+// A compiled module and an instance of it, for tests that call into one
+// module over and over.
+pub struct Instance {
+    pub bytecode: Bytecode,
+    pub vm: Vm,
+    pub imports: Imports,
+}
+
+impl Instance {
+    pub fn new(wat: &str) -> Self {
+        Self::with_imports(wat, Imports::new())
+    }
+
+    pub fn with_config(wat: &str, config: Config) -> Self {
+        let mut imports = Imports::new();
+        let (bytecode, vm) = instantiate_with_config(wat, &mut imports, config);
+        Self { bytecode, vm, imports }
+    }
+
+    pub fn with_imports(wat: &str, mut imports: Imports) -> Self {
+        let (bytecode, vm) = instantiate(wat, &mut imports);
+        Self { bytecode, vm, imports }
+    }
+
+    pub fn try_call(&mut self, name: &str, args: &[Value]) -> anyhow::Result<Return> {
+        self.vm.call(&self.bytecode, name, args, &mut self.imports)
+    }
+
+    // Calls a function returning a single value.
+    pub fn call(&mut self, name: &str, args: &[Value]) -> Value {
+        call(&mut self.vm, &self.bytecode, name, args, &mut self.imports)
+    }
+
+    pub fn call_i32(&mut self, name: &str, args: &[Value]) -> i32 {
+        self.call(name, args).as_i32().unwrap()
+    }
+
+    pub fn call_i64(&mut self, name: &str, args: &[Value]) -> i64 {
+        self.call(name, args).as_i64().unwrap()
+    }
+
+    // Floats are compared by their bits, so NaN payloads and zero signs count.
+    pub fn call_f32_bits(&mut self, name: &str, args: &[Value]) -> u32 {
+        self.call(name, args).as_f32().unwrap().to_bits()
+    }
+
+    pub fn call_f64_bits(&mut self, name: &str, args: &[Value]) -> u64 {
+        self.call(name, args).as_f64().unwrap().to_bits()
+    }
+
+    pub fn call_v128(&mut self, name: &str, args: &[Value]) -> u128 {
+        self.call(name, args).as_v128().unwrap()
+    }
+
+    // The kind of trap a call ends with. Panics if it doesn't trap.
+    pub fn trap(&mut self, name: &str, args: &[Value]) -> TrapKind {
+        let error = self.try_call(name, args).unwrap_err();
+        error.downcast::<Trap>().unwrap().kind()
+    }
+}
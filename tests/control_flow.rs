@@ -0,0 +1,99 @@
+mod common;
+
+use common::Instance;
+use weloce::{Return, Value};
+
+const FLOW: &str = r#"(module
+    ;; A branch out of a block carries the block's results and drops whatever
+    ;; else was pushed since it was entered.
+    (func (export "block") (param i32) (result i32)
+        (i32.const 1000)
+        (block $out (result i32)
+            (i32.const 1) (i32.const 2)
+            (br_if $out (i32.const 10) (local.get 0))
+            (drop) (drop) (drop) (i32.const 20))
+        (i32.add))
+    ;; A branch to a loop goes back to its start with the loop's parameters.
+    (func (export "loop") (param i32) (result i32)
+        (i32.const 0)
+        (local.get 0)
+        (loop $again (param i32 i32) (result i32)
+            (if (param i32 i32) (result i32) (i32.eqz (local.get 0))
+                (then (drop))
+                (else
+                    (local.set 0 (i32.sub (local.get 0) (i32.const 1)))
+                    (i32.add)
+                    (local.get 0)
+                    (br $again)))))
+    (func (export "if") (param i32) (result i32 i32)
+        (i32.const 7)
+        (if (param i32) (result i32 i32) (local.get 0)
+            (then (i32.const 1))
+            (else (i32.const 2))))
+    (func (export "if_without_else") (param i32) (result i32)
+        (local $result i32)
+        (local.set $result (i32.const 5))
+        (if (local.get 0) (then (local.set $result (i32.const 6))))
+        (local.get $result))
+    (func (export "br_table") (param i32) (result i32)
+        (block $default (result i32)
+            (block $two (result i32)
+                (block $one (result i32)
+                    (block $zero (result i32)
+                        (i32.const 100)
+                        (br_table $zero $one $two $default (local.get 0)))
+                    (i32.add (i32.const 0)) (return))
+                (i32.add (i32.const 1)) (return))
+            (i32.add (i32.const 2)) (return))
+        (i32.add (i32.const 3)))
+    ;; Branching to the function's own label returns from it.
+    (func (export "br_out") (param i32) (result i32)
+        (block
+            (drop (br_if 1 (i32.const 42) (local.get 0))))
+        (i32.const 0)))"#;
+
+#[test]
+fn branches_out_of_blocks_keep_their_results() {
+    let mut flow = Instance::new(FLOW);
+    assert_eq!(flow.call_i32("block", &[Value::i32(1)]), 1010);
+    assert_eq!(flow.call_i32("block", &[Value::i32(0)]), 1020);
+}
+
+#[test]
+fn branches_to_loops_pass_their_parameters() {
+    let mut flow = Instance::new(FLOW);
+    assert_eq!(flow.call_i32("loop", &[Value::i32(0)]), 0);
+    assert_eq!(flow.call_i32("loop", &[Value::i32(4)]), 10);
+    assert_eq!(flow.call_i32("loop", &[Value::i32(100)]), 5050);
+}
+
+#[test]
+fn if_takes_the_branch_its_condition_selects() {
+    let mut flow = Instance::new(FLOW);
+    for (condition, expected) in [(1, 1), (-1, 1), (0, 2)] {
+        match flow.try_call("if", &[Value::i32(condition)]).unwrap() {
+            Return::Multiple(values) => {
+                assert_eq!(values[0].as_i32(), Some(7));
+                assert_eq!(values[1].as_i32(), Some(expected));
+            },
+            result => panic!("Expected two values, got {:?}", result),
+        }
+    }
+    assert_eq!(flow.call_i32("if_without_else", &[Value::i32(1)]), 6);
+    assert_eq!(flow.call_i32("if_without_else", &[Value::i32(0)]), 5);
+}
+
+#[test]
+fn br_table_picks_its_target_and_falls_back_to_the_default() {
+    let mut flow = Instance::new(FLOW);
+    for (index, expected) in [(0, 100), (1, 101), (2, 102), (3, 103), (4, 103), (-1, 103)] {
+        assert_eq!(flow.call_i32("br_table", &[Value::i32(index)]), expected, "br_table index {index}");
+    }
+}
+
+#[test]
+fn branching_to_the_outermost_label_returns() {
+    let mut flow = Instance::new(FLOW);
+    assert_eq!(flow.call_i32("br_out", &[Value::i32(1)]), 42);
+    assert_eq!(flow.call_i32("br_out", &[Value::i32(0)]), 0);
+}
@@ -0,0 +1,138 @@
+mod common;
+
+use common::Instance;
+use weloce::Value;
+
+const OPS: &str = r#"(module
+    (func (export "f32.convert_i32_s") (param i32) (result f32) (f32.convert_i32_s (local.get 0)))
+    (func (export "f32.convert_i32_u") (param i32) (result f32) (f32.convert_i32_u (local.get 0)))
+    (func (export "f32.convert_i64_s") (param i64) (result f32) (f32.convert_i64_s (local.get 0)))
+    (func (export "f32.convert_i64_u") (param i64) (result f32) (f32.convert_i64_u (local.get 0)))
+    (func (export "f64.convert_i32_s") (param i32) (result f64) (f64.convert_i32_s (local.get 0)))
+    (func (export "f64.convert_i32_u") (param i32) (result f64) (f64.convert_i32_u (local.get 0)))
+    (func (export "f64.convert_i64_s") (param i64) (result f64) (f64.convert_i64_s (local.get 0)))
+    (func (export "f64.convert_i64_u") (param i64) (result f64) (f64.convert_i64_u (local.get 0)))
+    (func (export "i32.trunc_sat_f32_s") (param f32) (result i32) (i32.trunc_sat_f32_s (local.get 0)))
+    (func (export "i32.trunc_sat_f32_u") (param f32) (result i32) (i32.trunc_sat_f32_u (local.get 0)))
+    (func (export "i32.trunc_sat_f64_s") (param f64) (result i32) (i32.trunc_sat_f64_s (local.get 0)))
+    (func (export "i32.trunc_sat_f64_u") (param f64) (result i32) (i32.trunc_sat_f64_u (local.get 0)))
+    (func (export "i64.trunc_sat_f32_s") (param f32) (result i64) (i64.trunc_sat_f32_s (local.get 0)))
+    (func (export "i64.trunc_sat_f32_u") (param f32) (result i64) (i64.trunc_sat_f32_u (local.get 0)))
+    (func (export "i64.trunc_sat_f64_s") (param f64) (result i64) (i64.trunc_sat_f64_s (local.get 0)))
+    (func (export "i64.trunc_sat_f64_u") (param f64) (result i64) (i64.trunc_sat_f64_u (local.get 0)))
+    (func (export "i32.reinterpret_f32") (param f32) (result i32) (i32.reinterpret_f32 (local.get 0)))
+    (func (export "f32.reinterpret_i32") (param i32) (result f32) (f32.reinterpret_i32 (local.get 0)))
+    (func (export "i64.reinterpret_f64") (param f64) (result i64) (i64.reinterpret_f64 (local.get 0)))
+    (func (export "f64.reinterpret_i64") (param i64) (result f64) (f64.reinterpret_i64 (local.get 0)))
+    (func (export "f64.promote_f32") (param f32) (result f64) (f64.promote_f32 (local.get 0)))
+    (func (export "f32.demote_f64") (param f64) (result f32) (f32.demote_f64 (local.get 0)))
+    (func (export "f32.round_trip") (param i32) (result i32) (i32.reinterpret_f32 (f32.reinterpret_i32 (local.get 0))))
+    (func (export "f64.round_trip") (param i64) (result i64) (i64.reinterpret_f64 (f64.reinterpret_i64 (local.get 0)))))"#;
+
+#[test]
+fn integers_convert_to_floats_by_signedness() {
+    let mut ops = Instance::new(OPS);
+    assert_eq!(ops.call("f32.convert_i32_s", &[Value::i32(-1)]).as_f32(), Some(-1.0));
+    assert_eq!(ops.call("f32.convert_i32_u", &[Value::i32(-1)]).as_f32(), Some(4294967296.0));
+    assert_eq!(ops.call("f32.convert_i64_s", &[Value::i64(i64::MIN)]).as_f32(), Some(-9223372036854775808.0));
+    assert_eq!(ops.call("f32.convert_i64_u", &[Value::i64(-1)]).as_f32(), Some(18446744073709551616.0));
+    assert_eq!(ops.call("f64.convert_i32_s", &[Value::i32(i32::MIN)]).as_f64(), Some(-2147483648.0));
+    assert_eq!(ops.call("f64.convert_i32_u", &[Value::i32(-1)]).as_f64(), Some(4294967295.0));
+    assert_eq!(ops.call("f64.convert_i64_s", &[Value::i64(-1)]).as_f64(), Some(-1.0));
+    assert_eq!(ops.call("f64.convert_i64_u", &[Value::i64(i64::MIN)]).as_f64(), Some(9223372036854775808.0));
+}
+
+#[test]
+fn integer_to_float_conversions_round_once() {
+    let mut ops = Instance::new(OPS);
+    assert_eq!(ops.call("f32.convert_i32_s", &[Value::i32(16777217)]).as_f32(), Some(16777216.0));
+    assert_eq!(ops.call("f32.convert_i32_u", &[Value::i32(-129)]).as_f32(), Some(4294967040.0));
+    // Going through f64 first would round this down to 2^63.
+    let value = 0x8000_0080_0000_0001u64;
+    assert_eq!(ops.call("f32.convert_i64_u", &[Value::i64(value as i64)]).as_f32(), Some(value as f32));
+    assert_eq!(ops.call("f64.convert_i64_u", &[Value::i64(-1023)]).as_f64(), Some(18446744073709551616.0));
+    assert_eq!(ops.call("f64.convert_i64_u", &[Value::i64(-1025)]).as_f64(), Some(18446744073709549568.0));
+}
+
+#[test]
+fn reinterpretation_keeps_every_bit() {
+    let mut ops = Instance::new(OPS);
+    for bits in [0x7fa0_0001u32, 0xffc0_1234, 0x8000_0000, 0x3fc0_0000] {
+        assert_eq!(ops.call_f32_bits("f32.reinterpret_i32", &[Value::i32(bits as i32)]), bits);
+        assert_eq!(ops.call_i32("i32.reinterpret_f32", &[Value::f32(f32::from_bits(bits))]), bits as i32);
+        assert_eq!(ops.call_i32("f32.round_trip", &[Value::i32(bits as i32)]), bits as i32);
+    }
+    for bits in [0x7ff4_0000_0000_0001u64, 0xfff8_0000_0000_1234, 0x8000_0000_0000_0000, 0x3ff8_0000_0000_0000] {
+        assert_eq!(ops.call_f64_bits("f64.reinterpret_i64", &[Value::i64(bits as i64)]), bits);
+        assert_eq!(ops.call_i64("i64.reinterpret_f64", &[Value::f64(f64::from_bits(bits))]), bits as i64);
+        assert_eq!(ops.call_i64("f64.round_trip", &[Value::i64(bits as i64)]), bits as i64);
+    }
+}
+
+#[test]
+fn promotion_and_demotion() {
+    let mut ops = Instance::new(OPS);
+    assert_eq!(ops.call("f64.promote_f32", &[Value::f32(1.5)]).as_f64(), Some(1.5));
+    assert_eq!(ops.call("f64.promote_f32", &[Value::f32(f32::MAX)]).as_f64(), Some(f32::MAX as f64));
+    assert_eq!(ops.call_f64_bits("f64.promote_f32", &[Value::f32(-0.0)]), (-0.0f64).to_bits());
+    assert_eq!(ops.call("f64.promote_f32", &[Value::f32(f32::NEG_INFINITY)]).as_f64(), Some(f64::NEG_INFINITY));
+    assert_eq!(ops.call("f32.demote_f64", &[Value::f64(1.5)]).as_f32(), Some(1.5));
+    assert_eq!(ops.call("f32.demote_f64", &[Value::f64(1e300)]).as_f32(), Some(f32::INFINITY));
+    assert_eq!(ops.call_f32_bits("f32.demote_f64", &[Value::f64(-1e-300)]), (-0.0f32).to_bits());
+    // Ties round to even.
+    assert_eq!(ops.call("f32.demote_f64", &[Value::f64(1.0 + 2f64.powi(-24))]).as_f32(), Some(1.0));
+    assert_eq!(ops.call("f32.demote_f64", &[Value::f64(1.0 + 3.0 * 2f64.powi(-24))]).as_f32(), Some(1.0 + 2f32.powi(-22)));
+}
+
+#[test]
+fn saturating_truncation_to_i32() {
+    let mut ops = Instance::new(OPS);
+    for (name, min, max) in [("i32.trunc_sat_f32_s", i32::MIN, i32::MAX), ("i32.trunc_sat_f32_u", 0, -1)] {
+        assert_eq!(ops.call_i32(name, &[Value::f32(f32::NAN)]), 0, "{name}");
+        assert_eq!(ops.call_i32(name, &[Value::f32(f32::INFINITY)]), max, "{name}");
+        assert_eq!(ops.call_i32(name, &[Value::f32(f32::NEG_INFINITY)]), min, "{name}");
+    }
+    for (name, min, max) in [("i32.trunc_sat_f64_s", i32::MIN, i32::MAX), ("i32.trunc_sat_f64_u", 0, -1)] {
+        assert_eq!(ops.call_i32(name, &[Value::f64(-f64::NAN)]), 0, "{name}");
+        assert_eq!(ops.call_i32(name, &[Value::f64(f64::INFINITY)]), max, "{name}");
+        assert_eq!(ops.call_i32(name, &[Value::f64(f64::NEG_INFINITY)]), min, "{name}");
+    }
+    assert_eq!(ops.call_i32("i32.trunc_sat_f32_s", &[Value::f32(2147483648.0)]), i32::MAX);
+    assert_eq!(ops.call_i32("i32.trunc_sat_f32_s", &[Value::f32(2147483520.0)]), 2147483520);
+    assert_eq!(ops.call_i32("i32.trunc_sat_f32_s", &[Value::f32(-2147483648.0)]), i32::MIN);
+    assert_eq!(ops.call_i32("i32.trunc_sat_f32_s", &[Value::f32(-2147483904.0)]), i32::MIN);
+    assert_eq!(ops.call_i32("i32.trunc_sat_f32_u", &[Value::f32(4294967040.0)]), -256);
+    assert_eq!(ops.call_i32("i32.trunc_sat_f32_u", &[Value::f32(4294967296.0)]), -1);
+    assert_eq!(ops.call_i32("i32.trunc_sat_f32_u", &[Value::f32(-0.9)]), 0);
+    assert_eq!(ops.call_i32("i32.trunc_sat_f64_s", &[Value::f64(2147483647.9)]), i32::MAX);
+    assert_eq!(ops.call_i32("i32.trunc_sat_f64_s", &[Value::f64(-2147483648.9)]), i32::MIN);
+    assert_eq!(ops.call_i32("i32.trunc_sat_f64_s", &[Value::f64(-2147483649.0)]), i32::MIN);
+    assert_eq!(ops.call_i32("i32.trunc_sat_f64_u", &[Value::f64(4294967295.9)]), -1);
+    assert_eq!(ops.call_i32("i32.trunc_sat_f64_u", &[Value::f64(4294967296.0)]), -1);
+    assert_eq!(ops.call_i32("i32.trunc_sat_f64_u", &[Value::f64(-1.0)]), 0);
+}
+
+#[test]
+fn saturating_truncation_to_i64() {
+    let mut ops = Instance::new(OPS);
+    for (name, min, max) in [("i64.trunc_sat_f32_s", i64::MIN, i64::MAX), ("i64.trunc_sat_f32_u", 0, -1)] {
+        assert_eq!(ops.call_i64(name, &[Value::f32(f32::NAN)]), 0, "{name}");
+        assert_eq!(ops.call_i64(name, &[Value::f32(f32::INFINITY)]), max, "{name}");
+        assert_eq!(ops.call_i64(name, &[Value::f32(f32::NEG_INFINITY)]), min, "{name}");
+    }
+    for (name, min, max) in [("i64.trunc_sat_f64_s", i64::MIN, i64::MAX), ("i64.trunc_sat_f64_u", 0, -1)] {
+        assert_eq!(ops.call_i64(name, &[Value::f64(-f64::NAN)]), 0, "{name}");
+        assert_eq!(ops.call_i64(name, &[Value::f64(f64::INFINITY)]), max, "{name}");
+        assert_eq!(ops.call_i64(name, &[Value::f64(f64::NEG_INFINITY)]), min, "{name}");
+    }
+    assert_eq!(ops.call_i64("i64.trunc_sat_f32_s", &[Value::f32(9223372036854775808.0)]), i64::MAX);
+    assert_eq!(ops.call_i64("i64.trunc_sat_f32_s", &[Value::f32(-9223372036854775808.0)]), i64::MIN);
+    assert_eq!(ops.call_i64("i64.trunc_sat_f32_u", &[Value::f32(18446742974197923840.0)]), -1099511627776);
+    assert_eq!(ops.call_i64("i64.trunc_sat_f32_u", &[Value::f32(-0.9)]), 0);
+    assert_eq!(ops.call_i64("i64.trunc_sat_f64_s", &[Value::f64(9223372036854774784.0)]), 9223372036854774784);
+    assert_eq!(ops.call_i64("i64.trunc_sat_f64_s", &[Value::f64(9223372036854775808.0)]), i64::MAX);
+    assert_eq!(ops.call_i64("i64.trunc_sat_f64_s", &[Value::f64(-9223372036854777856.0)]), i64::MIN);
+    assert_eq!(ops.call_i64("i64.trunc_sat_f64_u", &[Value::f64(18446744073709549568.0)]), -2048);
+    assert_eq!(ops.call_i64("i64.trunc_sat_f64_u", &[Value::f64(18446744073709551616.0)]), -1);
+    assert_eq!(ops.call_i64("i64.trunc_sat_f64_u", &[Value::f64(-1.0)]), 0);
+}
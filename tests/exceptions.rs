@@ -0,0 +1,151 @@
+mod common;
+
+use common::{call, instantiate};
+use weloce::{Exception, Imports, Return, Trap, TrapKind, ValType, Value};
+
+#[test]
+fn try_table_catches_across_frames() {
+    let mut imports = Imports::new();
+    let (bytecode, mut vm) = instantiate(
+        r#"(module
+            (tag $e (param i32))
+            (func $inner (param i32) (throw $e (i32.mul (local.get 0) (i32.const 2))))
+            (func $middle (param i32) (call $inner (i32.add (local.get 0) (i32.const 1))))
+            (func (export "run") (param i32) (result i32)
+                (block $caught (result i32)
+                    (try_table (catch $e $caught) (call $middle (local.get 0)))
+                    (i32.const -1))))"#,
+        &mut imports,
+    );
+    assert_eq!(call(&mut vm, &bytecode, "run", &[Value::i32(20)], &mut imports).as_i32(), Some(42));
+}
+
+#[test]
+fn guest_catches_host_exceptions() {
+    let mut imports = Imports::new();
+    let tag = imports.add_tag("env", "error", vec![ValType::I32]);
+    imports.add_import("env", "fail", vec![], vec![], Box::new(move |_| Err(Exception::new(tag, vec![Value::i32(7)]).into())));
+    let (bytecode, mut vm) = instantiate(
+        r#"(module
+            (import "env" "error" (tag $error (param i32)))
+            (import "env" "fail" (func $fail))
+            (func (export "run") (result i32)
+                (block $caught (result i32)
+                    (try_table (catch $error $caught) (call $fail))
+                    (i32.const -1))))"#,
+        &mut imports,
+    );
+    assert_eq!(call(&mut vm, &bytecode, "run", &[], &mut imports).as_i32(), Some(7));
+}
+
+#[test]
+fn host_catches_guest_exceptions() {
+    let mut imports = Imports::new();
+    let tag = imports.add_tag("env", "error", vec![ValType::I64]);
+    let (bytecode, mut vm) = instantiate(
+        r#"(module
+            (import "env" "error" (tag $error (param i64)))
+            (func (export "run") (throw $error (i64.const -3))))"#,
+        &mut imports,
+    );
+    let error = vm.call(&bytecode, "run", &[], &mut imports).unwrap_err();
+    let exception = error.downcast::<Exception>().unwrap();
+    assert_eq!(exception.tag(), tag);
+    assert_eq!(exception.values()[0].as_i64(), Some(-3));
+}
+
+#[test]
+fn uncaught_tags_pass_through_other_handlers() {
+    let mut imports = Imports::new();
+    let tag = imports.add_tag("env", "b", vec![]);
+    let (bytecode, mut vm) = instantiate(
+        r#"(module
+            (import "env" "b" (tag $b))
+            (tag $a)
+            (func (export "run") (result i32)
+                (block $caught
+                    (try_table (catch $a $caught) (throw $b))
+                    (return (i32.const -1)))
+                (i32.const 1)))"#,
+        &mut imports,
+    );
+    let error = vm.call(&bytecode, "run", &[], &mut imports).unwrap_err();
+    assert_eq!(error.downcast::<Exception>().unwrap().tag(), tag);
+}
+
+#[test]
+fn host_exceptions_must_match_their_tag() {
+    let mut imports = Imports::new();
+    let tag = imports.add_tag("env", "error", vec![ValType::I32]);
+    imports.add_import("env", "fail", vec![], vec![], Box::new(move |_| Err(Exception::new(tag, vec![Value::f32(1.0)]).into())));
+    let (bytecode, mut vm) = instantiate(
+        r#"(module
+            (import "env" "error" (tag $error (param i32)))
+            (import "env" "fail" (func $fail))
+            (func (export "run") (result i32)
+                (block $caught (result i32)
+                    (try_table (catch $error $caught) (call $fail))
+                    (i32.const -1))))"#,
+        &mut imports,
+    );
+    let error = vm.call(&bytecode, "run", &[], &mut imports).unwrap_err();
+    assert!(!error.is::<Exception>());
+}
+
+const REFS: &str = r#"(module
+    (tag $e (param i32))
+    (func (export "catch") (param i32) (result exnref)
+        (block $caught (result exnref)
+            (try_table (catch_all_ref $caught) (throw $e (local.get 0)))
+            (unreachable)))
+    (func (export "rethrow") (param exnref) (result i32)
+        (block $caught (result i32)
+            (try_table (catch $e $caught) (throw_ref (local.get 0)))
+            (i32.const -1)))
+    (func (export "noop"))
+    ;; Catches `n` exceptions, keeping the one thrown with `keep` in a local,
+    ;; and throws it again at the end.
+    (func (export "churn") (param $n i32) (param $keep i32)
+        (local $i i32) (local $caught exnref) (local $kept exnref)
+        (loop $next
+            (local.set $caught
+                (block $caught (result exnref)
+                    (try_table (catch_all_ref $caught) (throw $e (local.get $i)))
+                    (unreachable)))
+            (if (i32.eq (local.get $i) (local.get $keep))
+                (then (local.set $kept (local.get $caught))))
+            (local.set $i (i32.add (local.get $i) (i32.const 1)))
+            (br_if $next (i32.lt_u (local.get $i) (local.get $n))))
+        (throw_ref (local.get $kept))))"#;
+
+#[test]
+fn throw_ref_rethrows_the_caught_exception() {
+    let mut imports = Imports::new();
+    let (bytecode, mut vm) = instantiate(REFS, &mut imports);
+    let exnref = call(&mut vm, &bytecode, "catch", &[Value::i32(9)], &mut imports);
+    assert_eq!(call(&mut vm, &bytecode, "rethrow", &[exnref], &mut imports).as_i32(), Some(9));
+}
+
+#[test]
+fn exnrefs_held_by_the_host_live_until_released() {
+    let mut imports = Imports::new();
+    let (bytecode, mut vm) = instantiate(REFS, &mut imports);
+    let exnref = call(&mut vm, &bytecode, "catch", &[Value::i32(5)], &mut imports);
+    vm.run(&bytecode, "noop", &mut imports).unwrap();
+    vm.run(&bytecode, "noop", &mut imports).unwrap();
+    assert_eq!(call(&mut vm, &bytecode, "rethrow", std::slice::from_ref(&exnref), &mut imports).as_i32(), Some(5));
+
+    vm.release_exn(&exnref);
+    vm.run(&bytecode, "noop", &mut imports).unwrap();
+    let error = vm.call(&bytecode, "rethrow", &[exnref], &mut imports).unwrap_err();
+    assert_eq!(error.downcast::<Trap>().unwrap().kind(), TrapKind::DanglingReference);
+}
+
+#[test]
+fn catching_in_a_loop_keeps_live_exceptions() {
+    let mut imports = Imports::new();
+    let (bytecode, mut vm) = instantiate(REFS, &mut imports);
+    let error = vm.call(&bytecode, "churn", &[Value::i32(100_000), Value::i32(3)], &mut imports).unwrap_err();
+    assert_eq!(error.downcast::<Exception>().unwrap().values()[0].as_i32(), Some(3));
+    assert!(matches!(vm.run(&bytecode, "noop", &mut imports).unwrap(), Return::Void));
+}
@@ -0,0 +1,119 @@
+mod common;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use common::instantiate;
+use weloce::{Exception, Imports, Return, ValType};
+
+// Counts how many of its kind were dropped.
+struct Tracked(Rc<RefCell<u32>>);
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        *self.0.borrow_mut() += 1;
+    }
+}
+
+const STORE: &str = r#"(module
+    (tag $boxed (export "boxed") (param externref))
+    (global $kept (mut exnref) (ref.null exn))
+    (func (export "keep") (param externref)
+        (global.set $kept
+            (block $caught (result exnref)
+                (try_table (catch_all_ref $caught) (throw $boxed (local.get 0)))
+                (unreachable))))
+    (func (export "forget") (global.set $kept (ref.null exn)))
+    (func (export "escape") (param externref) (throw $boxed (local.get 0)))
+    (func (export "noop")))"#;
+
+#[test]
+fn exceptions_kept_by_the_guest_keep_their_objects() {
+    let dropped = Rc::new(RefCell::new(0));
+    let mut imports = Imports::new();
+    let (bytecode, mut vm) = instantiate(STORE, &mut imports);
+    let object = vm.new_extern(Box::new(Tracked(dropped.clone()))).unwrap();
+    vm.call(&bytecode, "keep", std::slice::from_ref(&object), &mut imports).unwrap();
+    vm.release_extern(&object);
+    vm.run(&bytecode, "noop", &mut imports).unwrap();
+    assert_eq!(*dropped.borrow(), 0);
+    assert!(vm.extern_object(&object).is_some());
+
+    vm.run(&bytecode, "forget", &mut imports).unwrap();
+    vm.run(&bytecode, "noop", &mut imports).unwrap();
+    assert_eq!(*dropped.borrow(), 1);
+}
+
+#[test]
+fn uncaught_exceptions_hand_their_objects_to_the_host() {
+    let dropped = Rc::new(RefCell::new(0));
+    let mut imports = Imports::new();
+    let (bytecode, mut vm) = instantiate(STORE, &mut imports);
+    let object = vm.new_extern(Box::new(Tracked(dropped.clone()))).unwrap();
+    let error = vm.call(&bytecode, "escape", std::slice::from_ref(&object), &mut imports).unwrap_err();
+    let exception = error.downcast::<Exception>().unwrap();
+    vm.release_extern(&object);
+    vm.run(&bytecode, "noop", &mut imports).unwrap();
+    assert_eq!(*dropped.borrow(), 0);
+
+    vm.release_extern(&exception.values()[0]);
+    assert_eq!(*dropped.borrow(), 1);
+}
+
+#[test]
+fn imports_keep_arguments_they_retain() {
+    let dropped = Rc::new(RefCell::new(0));
+    let stashed = Rc::new(RefCell::new(Vec::new()));
+    let mut imports = Imports::new();
+    let stash = stashed.clone();
+    imports.add_import("env", "stash", vec![ValType::ExternRef], vec![], Box::new(move |args| {
+        stash.borrow_mut().push(args[0].clone());
+        Ok(Return::Void)
+    }));
+    let (bytecode, mut vm) = instantiate(
+        r#"(module
+            (import "env" "stash" (func $stash (param externref)))
+            (func (export "pass") (param externref) (call $stash (local.get 0)))
+            (func (export "noop")))"#,
+        &mut imports,
+    );
+    let kept = vm.new_extern(Box::new(Tracked(dropped.clone()))).unwrap();
+    let lost = vm.new_extern(Box::new(Tracked(dropped.clone()))).unwrap();
+    vm.call(&bytecode, "pass", std::slice::from_ref(&kept), &mut imports).unwrap();
+    vm.call(&bytecode, "pass", std::slice::from_ref(&lost), &mut imports).unwrap();
+    vm.retain_extern(&stashed.borrow()[0]);
+    vm.release_extern(&kept);
+    vm.release_extern(&lost);
+    vm.run(&bytecode, "noop", &mut imports).unwrap();
+    assert_eq!(*dropped.borrow(), 1);
+    assert!(vm.extern_object(&stashed.borrow()[0]).is_some());
+    assert!(vm.extern_object(&stashed.borrow()[1]).is_none());
+}
+
+#[test]
+fn objects_returned_to_the_host_live_until_released() {
+    let dropped = Rc::new(RefCell::new(0));
+    let mut imports = Imports::new();
+    let (bytecode, mut vm) = instantiate(
+        r#"(module
+            (global $held (mut externref) (ref.null extern))
+            (func (export "hold") (param externref) (global.set $held (local.get 0)))
+            (func (export "take") (result externref)
+                (global.get $held)
+                (global.set $held (ref.null extern)))
+            (func (export "noop")))"#,
+        &mut imports,
+    );
+    let object = vm.new_extern(Box::new(Tracked(dropped.clone()))).unwrap();
+    vm.call(&bytecode, "hold", std::slice::from_ref(&object), &mut imports).unwrap();
+    vm.release_extern(&object);
+    let Return::Single(taken) = vm.run(&bytecode, "take", &mut imports).unwrap() else {
+        panic!("Expected a single value");
+    };
+    vm.run(&bytecode, "noop", &mut imports).unwrap();
+    assert_eq!(*dropped.borrow(), 0);
+    assert!(vm.extern_object(&taken).is_some());
+
+    vm.release_extern(&taken);
+    assert_eq!(*dropped.borrow(), 1);
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use common::Instance;
+use weloce::Value;
+
+const CANONICAL_F32: u32 = 0x7fc0_0000;
+const CANONICAL_F64: u64 = 0x7ff8_0000_0000_0000;
+
+const OPS: &str = r#"(module
+    (func (export "f32.add") (param f32 f32) (result f32) (f32.add (local.get 0) (local.get 1)))
+    (func (export "f32.div") (param f32 f32) (result f32) (f32.div (local.get 0) (local.get 1)))
+    (func (export "f32.min") (param f32 f32) (result f32) (f32.min (local.get 0) (local.get 1)))
+    (func (export "f32.max") (param f32 f32) (result f32) (f32.max (local.get 0) (local.get 1)))
+    (func (export "f32.copysign") (param f32 f32) (result f32) (f32.copysign (local.get 0) (local.get 1)))
+    (func (export "f32.sqrt") (param f32) (result f32) (f32.sqrt (local.get 0)))
+    (func (export "f32.nearest") (param f32) (result f32) (f32.nearest (local.get 0)))
+    (func (export "f32.ceil") (param f32) (result f32) (f32.ceil (local.get 0)))
+    (func (export "f32.abs") (param f32) (result f32) (f32.abs (local.get 0)))
+    (func (export "f32.neg") (param f32) (result f32) (f32.neg (local.get 0)))
+    (func (export "f32.demote") (param f64) (result f32) (f32.demote_f64 (local.get 0)))
+    (func (export "f64.mul") (param f64 f64) (result f64) (f64.mul (local.get 0) (local.get 1)))
+    (func (export "f64.min") (param f64 f64) (result f64) (f64.min (local.get 0) (local.get 1)))
+    (func (export "f64.max") (param f64 f64) (result f64) (f64.max (local.get 0) (local.get 1)))
+    (func (export "f64.nearest") (param f64) (result f64) (f64.nearest (local.get 0)))
+    (func (export "f64.promote") (param f32) (result f64) (f64.promote_f32 (local.get 0))))"#;
+
+fn f32_bits(bits: u32) -> Value {
+    Value::f32(f32::from_bits(bits))
+}
+
+fn f64_bits(bits: u64) -> Value {
+    Value::f64(f64::from_bits(bits))
+}
+
+#[test]
+fn arithmetic_produces_canonical_nans() {
+    let mut ops = Instance::new(OPS);
+    let negative_nan = f32_bits(0xffc0_1234);
+    assert_eq!(ops.call_f32_bits("f32.add", &[negative_nan.clone(), Value::f32(1.0)]), CANONICAL_F32);
+    assert_eq!(ops.call_f32_bits("f32.div", &[Value::f32(0.0), Value::f32(0.0)]), CANONICAL_F32);
+    assert_eq!(ops.call_f32_bits("f32.sqrt", &[Value::f32(-1.0)]), CANONICAL_F32);
+    assert_eq!(ops.call_f32_bits("f32.ceil", std::slice::from_ref(&negative_nan)), CANONICAL_F32);
+    assert_eq!(ops.call_f32_bits("f32.nearest", &[negative_nan]), CANONICAL_F32);
+    assert_eq!(ops.call_f64_bits("f64.mul", &[Value::f64(f64::INFINITY), Value::f64(0.0)]), CANONICAL_F64);
+    assert_eq!(ops.call_f32_bits("f32.demote", &[f64_bits(0xfff0_0000_0000_0001)]), CANONICAL_F32);
+    assert_eq!(ops.call_f64_bits("f64.promote", &[f32_bits(0xff80_0001)]), CANONICAL_F64);
+}
+
+#[test]
+fn sign_operations_keep_nan_bits() {
+    let mut ops = Instance::new(OPS);
+    assert_eq!(ops.call_f32_bits("f32.abs", &[f32_bits(0xffc0_1234)]), 0x7fc0_1234);
+    assert_eq!(ops.call_f32_bits("f32.neg", &[f32_bits(0x7fc0_1234)]), 0xffc0_1234);
+    assert_eq!(ops.call_f32_bits("f32.copysign", &[f32_bits(0x7fa0_0001), Value::f32(-1.0)]), 0xffa0_0001);
+}
+
+#[test]
+fn min_and_max_propagate_nan_and_order_zeros() {
+    let mut ops = Instance::new(OPS);
+    let nan = f32_bits(0xffa0_0001);
+    assert_eq!(ops.call_f32_bits("f32.min", &[nan.clone(), Value::f32(1.0)]), CANONICAL_F32);
+    assert_eq!(ops.call_f32_bits("f32.max", &[Value::f32(1.0), nan]), CANONICAL_F32);
+    assert_eq!(ops.call_f32_bits("f32.min", &[Value::f32(0.0), Value::f32(-0.0)]), (-0.0f32).to_bits());
+    assert_eq!(ops.call_f32_bits("f32.min", &[Value::f32(-0.0), Value::f32(0.0)]), (-0.0f32).to_bits());
+    assert_eq!(ops.call_f32_bits("f32.max", &[Value::f32(-0.0), Value::f32(0.0)]), 0.0f32.to_bits());
+    assert_eq!(ops.call_f32_bits("f32.max", &[Value::f32(0.0), Value::f32(-0.0)]), 0.0f32.to_bits());
+    assert_eq!(ops.call_f64_bits("f64.min", &[Value::f64(f64::NAN), Value::f64(f64::NEG_INFINITY)]), CANONICAL_F64);
+    assert_eq!(ops.call_f64_bits("f64.min", &[Value::f64(0.0), Value::f64(-0.0)]), (-0.0f64).to_bits());
+    assert_eq!(ops.call_f64_bits("f64.max", &[Value::f64(-0.0), Value::f64(0.0)]), 0.0f64.to_bits());
+}
+
+#[test]
+fn nearest_rounds_ties_to_even() {
+    let mut ops = Instance::new(OPS);
+    for (value, expected) in [(0.5f32, 0.0f32), (1.5, 2.0), (2.5, 2.0), (-2.5, -2.0), (-0.5, -0.0)] {
+        assert_eq!(ops.call_f32_bits("f32.nearest", &[Value::f32(value)]), expected.to_bits(), "nearest({value})");
+    }
+    for (value, expected) in [(0.5f64, 0.0f64), (3.5, 4.0), (-4.5, -4.0), (4503599627370497.0, 4503599627370497.0)] {
+        assert_eq!(ops.call_f64_bits("f64.nearest", &[Value::f64(value)]), expected.to_bits(), "nearest({value})");
+    }
+}
+
+#[test]
+fn copysign_takes_only_the_sign() {
+    let mut ops = Instance::new(OPS);
+    assert_eq!(ops.call_f32_bits("f32.copysign", &[Value::f32(2.5), Value::f32(-0.0)]), (-2.5f32).to_bits());
+    assert_eq!(ops.call_f32_bits("f32.copysign", &[Value::f32(-2.5), Value::f32(f32::INFINITY)]), 2.5f32.to_bits());
+    assert_eq!(ops.call_f32_bits("f32.copysign", &[Value::f32(1.0), f32_bits(0xffc0_0000)]), (-1.0f32).to_bits());
+}
@@ -0,0 +1,97 @@
+mod common;
+
+use common::{call, instantiate};
+use weloce::{Imports, Return, ValType, Value};
+
+const FUNCTIONS: usize = 48;
+
+fn next(index: usize) -> usize {
+    (index * 7 + 3) % FUNCTIONS
+}
+
+// Function `i` returns `i` when `n` is zero, and otherwise adds `i` to the
+// result of calling function `next(i)` with `n - 1`.
+fn expected(index: usize, n: i32) -> i32 {
+    if n == 0 {
+        index as i32
+    } else {
+        expected(next(index), n - 1) + index as i32
+    }
+}
+
+fn chained_functions() -> String {
+    (0..FUNCTIONS)
+        .map(|index| {
+            format!(
+                r#"(func $f{index} (export "f{index}") (param i32) (result i32)
+                    (if (result i32) (i32.eqz (local.get 0))
+                        (then (i32.const {index}))
+                        (else (i32.add (call $f{next} (i32.sub (local.get 0) (i32.const 1))) (i32.const {index})))))"#,
+                next = next(index),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn dozens_of_mutually_calling_functions() {
+    let mut imports = Imports::new();
+    let (bytecode, mut vm) = instantiate(&format!("(module {})", chained_functions()), &mut imports);
+    for index in [0, 1, 17, FUNCTIONS - 1] {
+        let result = call(&mut vm, &bytecode, &format!("f{index}"), &[Value::i32(100)], &mut imports).as_i32().unwrap();
+        assert_eq!(result, expected(index, 100));
+    }
+}
+
+#[test]
+fn mutually_recursive_functions() {
+    let mut imports = Imports::new();
+    let (bytecode, mut vm) = instantiate(
+        r#"(module
+            (func $is_even (export "is_even") (param i32) (result i32)
+                (if (result i32) (i32.eqz (local.get 0))
+                    (then (i32.const 1))
+                    (else (call $is_odd (i32.sub (local.get 0) (i32.const 1))))))
+            (func $is_odd (export "is_odd") (param i32) (result i32)
+                (if (result i32) (i32.eqz (local.get 0))
+                    (then (i32.const 0))
+                    (else (call $is_even (i32.sub (local.get 0) (i32.const 1)))))))"#,
+        &mut imports,
+    );
+    assert_eq!(call(&mut vm, &bytecode, "is_even", &[Value::i32(200)], &mut imports).as_i32().unwrap(), 1);
+    assert_eq!(call(&mut vm, &bytecode, "is_odd", &[Value::i32(200)], &mut imports).as_i32().unwrap(), 0);
+    assert_eq!(call(&mut vm, &bytecode, "is_odd", &[Value::i32(77)], &mut imports).as_i32().unwrap(), 1);
+}
+
+#[test]
+fn imports_come_before_definitions() {
+    let mut imports = Imports::new();
+    imports.add_import("env", "double", vec![ValType::I32], vec![ValType::I32], Box::new(|args| {
+        Ok(Return::Single(Value::i32(args[0].as_i32().unwrap() * 2)))
+    }));
+    imports.add_global("env", "offset", Value::i32(1000), false);
+    imports.add_import("env", "negate", vec![ValType::I32], vec![ValType::I32], Box::new(|args| {
+        Ok(Return::Single(Value::i32(-args[0].as_i32().unwrap())))
+    }));
+    let (bytecode, mut vm) = instantiate(
+        &format!(
+            r#"(module
+                (import "env" "double" (func $double (param i32) (result i32)))
+                (import "env" "offset" (global $offset i32))
+                (import "env" "negate" (func $negate (param i32) (result i32)))
+                {}
+                (func (export "combined") (param i32) (result i32)
+                    (i32.add
+                        (call $negate (call $double (call $f5 (local.get 0))))
+                        (global.get $offset))))"#,
+            chained_functions(),
+        ),
+        &mut imports,
+    );
+    for index in [0, 5, FUNCTIONS - 1] {
+        let result = call(&mut vm, &bytecode, &format!("f{index}"), &[Value::i32(10)], &mut imports).as_i32().unwrap();
+        assert_eq!(result, expected(index, 10));
+    }
+    let result = call(&mut vm, &bytecode, "combined", &[Value::i32(10)], &mut imports).as_i32().unwrap();
+    assert_eq!(result, 1000 - 2 * expected(5, 10));
+}
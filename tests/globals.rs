@@ -0,0 +1,92 @@
+mod common;
+
+use common::{call, instantiate};
+use weloce::{compile_wasm, Imports, Value, Vm};
+
+const COUNTER: &str = r#"(module
+    (global $count (mut i32) (i32.const 0))
+    (func (export "bump") (result i32)
+        (global.set $count (i32.add (global.get $count) (i32.const 1)))
+        (global.get $count)))"#;
+
+#[test]
+fn globals_keep_their_value_across_calls() {
+    let mut imports = Imports::new();
+    let (bytecode, mut vm) = instantiate(COUNTER, &mut imports);
+    assert_eq!(call(&mut vm, &bytecode, "bump", &[], &mut imports).as_i32(), Some(1));
+    assert_eq!(call(&mut vm, &bytecode, "bump", &[], &mut imports).as_i32(), Some(2));
+}
+
+#[test]
+fn calling_before_instantiate_is_an_error() {
+    let mut imports = Imports::new();
+    let bytecode = compile_wasm(&wat::parse_str(COUNTER).unwrap(), &imports).unwrap();
+    let mut vm = Vm::new();
+    assert!(vm.call(&bytecode, "bump", &[], &mut imports).is_err());
+}
+
+#[test]
+fn calling_into_another_module_is_an_error() {
+    let mut imports = Imports::new();
+    let (_, mut vm) = instantiate("(module)", &mut imports);
+    let bytecode = compile_wasm(&wat::parse_str(COUNTER).unwrap(), &imports).unwrap();
+    assert!(vm.call(&bytecode, "bump", &[], &mut imports).is_err());
+    assert!(vm.call(&bytecode, "bump", &[Value::i32(0)], &mut imports).is_err());
+}
+
+#[test]
+fn initializers_read_imported_globals() {
+    let mut imports = Imports::new();
+    imports.add_global("env", "base", Value::i32(1024), false);
+    let (bytecode, mut vm) = instantiate(
+        r#"(module
+            (import "env" "base" (global $base i32))
+            (global $end i32 (i32.add (global.get $base) (i32.mul (i32.const 16) (i32.const 4))))
+            (global $wide i64 (i64.sub (i64.const 10) (i64.const 20)))
+            (func (export "base") (result i32) (global.get $base))
+            (func (export "end") (result i32) (global.get $end))
+            (func (export "wide") (result i64) (global.get $wide)))"#,
+        &mut imports,
+    );
+    assert_eq!(call(&mut vm, &bytecode, "base", &[], &mut imports).as_i32(), Some(1024));
+    assert_eq!(call(&mut vm, &bytecode, "end", &[], &mut imports).as_i32(), Some(1088));
+    assert_eq!(call(&mut vm, &bytecode, "wide", &[], &mut imports).as_i64(), Some(-10));
+}
+
+#[test]
+fn imported_globals_are_read_at_instantiation() {
+    let wat = r#"(module
+        (import "env" "base" (global $base i32))
+        (func (export "base") (result i32) (global.get $base)))"#;
+    let mut imports = Imports::new();
+    imports.add_global("env", "base", Value::i32(1), false);
+    let bytecode = compile_wasm(&wat::parse_str(wat).unwrap(), &imports).unwrap();
+    imports.add_global("env", "base", Value::i32(2), false);
+    let mut vm = Vm::new();
+    vm.instantiate(&bytecode, &mut imports).unwrap();
+    assert_eq!(call(&mut vm, &bytecode, "base", &[], &mut imports).as_i32(), Some(2));
+
+    imports.add_global("env", "base", Value::i64(3), false);
+    assert!(Vm::new().instantiate(&bytecode, &mut imports).is_err());
+}
+
+#[test]
+fn mutable_imported_globals_are_shared() {
+    let wat = r#"(module
+        (import "env" "counter" (global $counter (mut i32)))
+        (func (export "bump") (result i32)
+            (global.set $counter (i32.add (global.get $counter) (i32.const 1)))
+            (global.get $counter)))"#;
+    let mut imports = Imports::new();
+    imports.add_global("env", "counter", Value::i32(10), true);
+    let (bytecode, mut first) = instantiate(wat, &mut imports);
+    let mut second = Vm::new();
+    second.instantiate(&bytecode, &mut imports).unwrap();
+
+    assert_eq!(call(&mut first, &bytecode, "bump", &[], &mut imports).as_i32(), Some(11));
+    assert_eq!(call(&mut second, &bytecode, "bump", &[], &mut imports).as_i32(), Some(12));
+    assert_eq!(imports.global("env", "counter").unwrap().as_i32(), Some(12));
+
+    imports.add_global("env", "counter", Value::i32(100), true);
+    assert_eq!(call(&mut first, &bytecode, "bump", &[], &mut imports).as_i32(), Some(101));
+}
@@ -0,0 +1,106 @@
+mod common;
+
+use common::Instance;
+use weloce::{TrapKind, Value};
+
+const OPS: &str = r#"(module
+    (func (export "rotl") (param i32 i32) (result i32) (i32.rotl (local.get 0) (local.get 1)))
+    (func (export "rotr") (param i32 i32) (result i32) (i32.rotr (local.get 0) (local.get 1)))
+    (func (export "shl") (param i32 i32) (result i32) (i32.shl (local.get 0) (local.get 1)))
+    (func (export "shr_s") (param i32 i32) (result i32) (i32.shr_s (local.get 0) (local.get 1)))
+    (func (export "shr_u") (param i32 i32) (result i32) (i32.shr_u (local.get 0) (local.get 1)))
+    (func (export "lt_s") (param i32 i32) (result i32) (i32.lt_s (local.get 0) (local.get 1)))
+    (func (export "lt_u") (param i32 i32) (result i32) (i32.lt_u (local.get 0) (local.get 1)))
+    (func (export "gt_s") (param i32 i32) (result i32) (i32.gt_s (local.get 0) (local.get 1)))
+    (func (export "gt_u") (param i32 i32) (result i32) (i32.gt_u (local.get 0) (local.get 1)))
+    (func (export "le_s") (param i32 i32) (result i32) (i32.le_s (local.get 0) (local.get 1)))
+    (func (export "le_u") (param i32 i32) (result i32) (i32.le_u (local.get 0) (local.get 1)))
+    (func (export "ge_s") (param i32 i32) (result i32) (i32.ge_s (local.get 0) (local.get 1)))
+    (func (export "ge_u") (param i32 i32) (result i32) (i32.ge_u (local.get 0) (local.get 1)))
+    (func (export "div_s") (param i32 i32) (result i32) (i32.div_s (local.get 0) (local.get 1)))
+    (func (export "div_u") (param i32 i32) (result i32) (i32.div_u (local.get 0) (local.get 1)))
+    (func (export "rem_s") (param i32 i32) (result i32) (i32.rem_s (local.get 0) (local.get 1)))
+    (func (export "rem_u") (param i32 i32) (result i32) (i32.rem_u (local.get 0) (local.get 1)))
+    (func (export "clz") (param i32) (result i32) (i32.clz (local.get 0)))
+    (func (export "ctz") (param i32) (result i32) (i32.ctz (local.get 0)))
+    (func (export "popcnt") (param i32) (result i32) (i32.popcnt (local.get 0)))
+    (func (export "extend8_s") (param i32) (result i32) (i32.extend8_s (local.get 0)))
+    (func (export "extend16_s") (param i32) (result i32) (i32.extend16_s (local.get 0))))"#;
+
+fn binary(ops: &mut Instance, name: &str, a: i32, b: i32) -> i32 {
+    ops.call_i32(name, &[Value::i32(a), Value::i32(b)])
+}
+
+fn unary(ops: &mut Instance, name: &str, a: i32) -> i32 {
+    ops.call_i32(name, &[Value::i32(a)])
+}
+
+#[test]
+fn rotations_wrap_bits_around() {
+    let mut ops = Instance::new(OPS);
+    assert_eq!(binary(&mut ops, "rotl", 0x8000_0001u32 as i32, 1), 3);
+    assert_eq!(binary(&mut ops, "rotr", 3, 1), 0x8000_0001u32 as i32);
+    assert_eq!(binary(&mut ops, "rotl", 0x1234_5678, 36), 0x2345_6781);
+    assert_eq!(binary(&mut ops, "rotr", 0x1234_5678, -4), 0x2345_6781);
+}
+
+#[test]
+fn bit_counts() {
+    let mut ops = Instance::new(OPS);
+    assert_eq!(unary(&mut ops, "clz", 0), 32);
+    assert_eq!(unary(&mut ops, "clz", 1), 31);
+    assert_eq!(unary(&mut ops, "clz", -1), 0);
+    assert_eq!(unary(&mut ops, "ctz", 0), 32);
+    assert_eq!(unary(&mut ops, "ctz", i32::MIN), 31);
+    assert_eq!(unary(&mut ops, "ctz", 12), 2);
+    assert_eq!(unary(&mut ops, "popcnt", 0), 0);
+    assert_eq!(unary(&mut ops, "popcnt", -1), 32);
+    assert_eq!(unary(&mut ops, "popcnt", 0x0f0f_0001), 9);
+}
+
+#[test]
+fn shift_counts_are_taken_modulo_32() {
+    let mut ops = Instance::new(OPS);
+    assert_eq!(binary(&mut ops, "shl", 1, 33), 2);
+    assert_eq!(binary(&mut ops, "shl", 1, 32), 1);
+    assert_eq!(binary(&mut ops, "shr_u", i32::MIN, 63), 1);
+    assert_eq!(binary(&mut ops, "shr_s", i32::MIN, 63), -1);
+    assert_eq!(binary(&mut ops, "shr_s", -8, -31), -4);
+}
+
+#[test]
+fn comparisons_follow_signedness() {
+    let mut ops = Instance::new(OPS);
+    assert_eq!(binary(&mut ops, "lt_s", -1, 1), 1);
+    assert_eq!(binary(&mut ops, "lt_u", -1, 1), 0);
+    assert_eq!(binary(&mut ops, "gt_s", -1, 1), 0);
+    assert_eq!(binary(&mut ops, "gt_u", -1, 1), 1);
+    assert_eq!(binary(&mut ops, "le_s", i32::MIN, i32::MAX), 1);
+    assert_eq!(binary(&mut ops, "le_u", i32::MIN, i32::MAX), 0);
+    assert_eq!(binary(&mut ops, "ge_s", 5, 5), 1);
+    assert_eq!(binary(&mut ops, "ge_u", 0, -1), 0);
+}
+
+#[test]
+fn division_follows_signedness() {
+    let mut ops = Instance::new(OPS);
+    assert_eq!(binary(&mut ops, "div_s", -7, 2), -3);
+    assert_eq!(binary(&mut ops, "div_u", -7, 2), 0x7fff_fffc);
+    assert_eq!(binary(&mut ops, "rem_s", -7, 2), -1);
+    assert_eq!(binary(&mut ops, "rem_s", 7, -2), 1);
+    assert_eq!(binary(&mut ops, "rem_u", -7, 2), 1);
+    assert_eq!(binary(&mut ops, "rem_s", i32::MIN, -1), 0);
+    assert_eq!(ops.trap("div_s", &[Value::i32(i32::MIN), Value::i32(-1)]), TrapKind::IntegerOverflow);
+    assert_eq!(binary(&mut ops, "div_u", i32::MIN, -1), 0);
+}
+
+#[test]
+fn sign_extension_from_narrow_widths() {
+    let mut ops = Instance::new(OPS);
+    assert_eq!(unary(&mut ops, "extend8_s", 0x7f), 0x7f);
+    assert_eq!(unary(&mut ops, "extend8_s", 0x80), -128);
+    assert_eq!(unary(&mut ops, "extend8_s", 0x1234_56ff), -1);
+    assert_eq!(unary(&mut ops, "extend16_s", 0x7fff), 0x7fff);
+    assert_eq!(unary(&mut ops, "extend16_s", 0x8000), -32768);
+    assert_eq!(unary(&mut ops, "extend16_s", 0x1234_8001), -32767);
+}
@@ -0,0 +1,80 @@
+mod common;
+
+use common::Instance;
+use weloce::Value;
+
+const OPS: &str = r#"(module
+    (func (export "add") (param i64 i64) (result i64) (i64.add (local.get 0) (local.get 1)))
+    (func (export "sub") (param i64 i64) (result i64) (i64.sub (local.get 0) (local.get 1)))
+    (func (export "mul") (param i64 i64) (result i64) (i64.mul (local.get 0) (local.get 1)))
+    (func (export "div_s") (param i64 i64) (result i64) (i64.div_s (local.get 0) (local.get 1)))
+    (func (export "div_u") (param i64 i64) (result i64) (i64.div_u (local.get 0) (local.get 1)))
+    (func (export "rem_s") (param i64 i64) (result i64) (i64.rem_s (local.get 0) (local.get 1)))
+    (func (export "rem_u") (param i64 i64) (result i64) (i64.rem_u (local.get 0) (local.get 1)))
+    (func (export "shl") (param i64 i64) (result i64) (i64.shl (local.get 0) (local.get 1)))
+    (func (export "shr_s") (param i64 i64) (result i64) (i64.shr_s (local.get 0) (local.get 1)))
+    (func (export "shr_u") (param i64 i64) (result i64) (i64.shr_u (local.get 0) (local.get 1)))
+    (func (export "rotl") (param i64 i64) (result i64) (i64.rotl (local.get 0) (local.get 1)))
+    (func (export "rotr") (param i64 i64) (result i64) (i64.rotr (local.get 0) (local.get 1)))
+    (func (export "wrap") (param i64) (result i32) (i32.wrap_i64 (local.get 0)))
+    (func (export "extend_s") (param i32) (result i64) (i64.extend_i32_s (local.get 0)))
+    (func (export "extend_u") (param i32) (result i64) (i64.extend_i32_u (local.get 0)))
+    ;; Negative i32 results feeding i64 operations.
+    (func (export "extend_u_of_sum") (param i32 i32) (result i64)
+        (i64.extend_i32_u (i32.add (local.get 0) (local.get 1))))
+    (func (export "wrap_then_extend_u") (param i64) (result i64)
+        (i64.extend_i32_u (i32.wrap_i64 (local.get 0))))
+    (func (export "overflowing_product_shr_u") (param i64 i64) (result i64)
+        (i64.shr_u (i64.mul (local.get 0) (local.get 1)) (i64.const 32))))"#;
+
+fn binary(ops: &mut Instance, name: &str, a: i64, b: i64) -> i64 {
+    ops.call_i64(name, &[Value::i64(a), Value::i64(b)])
+}
+
+#[test]
+fn arithmetic_wraps() {
+    let mut ops = Instance::new(OPS);
+    assert_eq!(binary(&mut ops, "add", i64::MAX, 1), i64::MIN);
+    assert_eq!(binary(&mut ops, "sub", i64::MIN, 1), i64::MAX);
+    assert_eq!(binary(&mut ops, "mul", i64::MAX, 2), -2);
+    assert_eq!(binary(&mut ops, "mul", 0x1_0000_0000, 0x1_0000_0000), 0);
+    assert_eq!(binary(&mut ops, "div_s", -7, 2), -3);
+    assert_eq!(binary(&mut ops, "div_u", -7, 2), 0x7fff_ffff_ffff_fffc);
+    assert_eq!(binary(&mut ops, "rem_s", -7, 2), -1);
+    assert_eq!(binary(&mut ops, "rem_u", -7, 2), 1);
+}
+
+#[test]
+fn shift_counts_are_taken_modulo_64() {
+    let mut ops = Instance::new(OPS);
+    assert_eq!(binary(&mut ops, "shl", 1, 64), 1);
+    assert_eq!(binary(&mut ops, "shl", 1, 65), 2);
+    assert_eq!(binary(&mut ops, "shl", 1, 127), i64::MIN);
+    assert_eq!(binary(&mut ops, "shr_u", i64::MIN, 127), 1);
+    assert_eq!(binary(&mut ops, "shr_s", i64::MIN, 127), -1);
+    assert_eq!(binary(&mut ops, "shr_s", -8, -63), -4);
+    assert_eq!(binary(&mut ops, "rotl", i64::MIN | 1, 65), 3);
+    assert_eq!(binary(&mut ops, "rotr", 3, 64), 3);
+    assert_eq!(binary(&mut ops, "rotr", 3, 129), i64::MIN | 1);
+    assert_eq!(binary(&mut ops, "rotl", 0x0123_4567_89ab_cdef, -4), 0xf012_3456_789a_bcdeu64 as i64);
+}
+
+#[test]
+fn conversions_between_widths() {
+    let mut ops = Instance::new(OPS);
+    assert_eq!(ops.call_i32("wrap", &[Value::i64(0x1_ffff_fffe)]), -2);
+    assert_eq!(ops.call_i32("wrap", &[Value::i64(i64::MIN)]), 0);
+    assert_eq!(ops.call_i64("extend_s", &[Value::i32(-1)]), -1);
+    assert_eq!(ops.call_i64("extend_s", &[Value::i32(i32::MAX)]), i32::MAX as i64);
+    assert_eq!(ops.call_i64("extend_u", &[Value::i32(-1)]), 0xffff_ffff);
+    assert_eq!(ops.call_i64("extend_u", &[Value::i32(i32::MIN)]), 0x8000_0000);
+}
+
+#[test]
+fn i32_results_do_not_leak_into_i64_results() {
+    let mut ops = Instance::new(OPS);
+    assert_eq!(ops.call_i64("extend_u_of_sum", &[Value::i32(-2), Value::i32(1)]), 0xffff_ffff);
+    assert_eq!(ops.call_i64("extend_u_of_sum", &[Value::i32(i32::MAX), Value::i32(1)]), 0x8000_0000);
+    assert_eq!(ops.call_i64("wrap_then_extend_u", &[Value::i64(-1)]), 0xffff_ffff);
+    assert_eq!(ops.call_i64("overflowing_product_shr_u", &[Value::i64(i64::MAX), Value::i64(4)]), 0xffff_ffff);
+}
@@ -0,0 +1,101 @@
+mod common;
+
+use common::Instance;
+use weloce::{compile_wasm, Imports, TrapKind, Value, Vm};
+
+const MEMORY: &str = r#"(module
+    (memory 1)
+    (data (i32.const 16) "\80\ff\7f\81\02\03\04\05")
+    (data (i32.const 65534) "\aa\bb")
+    (func (export "i32.load8_s") (param i32) (result i32) (i32.load8_s (local.get 0)))
+    (func (export "i32.load8_u") (param i32) (result i32) (i32.load8_u (local.get 0)))
+    (func (export "i32.load16_s") (param i32) (result i32) (i32.load16_s (local.get 0)))
+    (func (export "i32.load16_u") (param i32) (result i32) (i32.load16_u (local.get 0)))
+    (func (export "i32.load") (param i32) (result i32) (i32.load (local.get 0)))
+    (func (export "i64.load8_s") (param i32) (result i64) (i64.load8_s (local.get 0)))
+    (func (export "i64.load8_u") (param i32) (result i64) (i64.load8_u (local.get 0)))
+    (func (export "i64.load16_s") (param i32) (result i64) (i64.load16_s (local.get 0)))
+    (func (export "i64.load16_u") (param i32) (result i64) (i64.load16_u (local.get 0)))
+    (func (export "i64.load32_s") (param i32) (result i64) (i64.load32_s (local.get 0)))
+    (func (export "i64.load32_u") (param i32) (result i64) (i64.load32_u (local.get 0)))
+    (func (export "i64.load") (param i32) (result i64) (i64.load (local.get 0)))
+    (func (export "i32.store8") (param i32 i32) (i32.store8 (local.get 0) (local.get 1)))
+    (func (export "i32.store16") (param i32 i32) (i32.store16 (local.get 0) (local.get 1)))
+    (func (export "i64.store8") (param i32 i64) (i64.store8 (local.get 0) (local.get 1)))
+    (func (export "i64.store16") (param i32 i64) (i64.store16 (local.get 0) (local.get 1)))
+    (func (export "i64.store32") (param i32 i64) (i64.store32 (local.get 0) (local.get 1)))
+    (func (export "f64.round_trip") (param i32 f64) (result f64)
+        (f64.store (local.get 0) (local.get 1))
+        (f64.load (local.get 0)))
+    (func (export "load_offset") (param i32) (result i32) (i32.load8_u offset=4 (local.get 0)))
+    (func (export "store_offset") (param i32 i32) (i32.store8 offset=1 (local.get 0) (local.get 1)))
+    (func (export "load_high_offset") (param i32) (result i32) (i32.load offset=65532 (local.get 0)))
+    (func (export "load_max_offset") (param i32) (result i32) (i32.load8_u offset=0xffffffff (local.get 0))))"#;
+
+fn at(address: i32) -> [Value; 1] {
+    [Value::i32(address)]
+}
+
+#[test]
+fn narrow_loads_extend_by_signedness() {
+    let mut memory = Instance::new(MEMORY);
+    assert_eq!(memory.call_i32("i32.load8_s", &at(16)), -128);
+    assert_eq!(memory.call_i32("i32.load8_u", &at(16)), 0x80);
+    assert_eq!(memory.call_i32("i32.load16_s", &at(16)), -128);
+    assert_eq!(memory.call_i32("i32.load16_u", &at(16)), 0xff80);
+    assert_eq!(memory.call_i32("i32.load16_s", &at(18)), -32385);
+    assert_eq!(memory.call_i32("i32.load", &at(16)), 0x817f_ff80u32 as i32);
+    assert_eq!(memory.call_i64("i64.load8_s", &at(16)), -128);
+    assert_eq!(memory.call_i64("i64.load8_u", &at(16)), 0x80);
+    assert_eq!(memory.call_i64("i64.load16_s", &at(16)), -128);
+    assert_eq!(memory.call_i64("i64.load16_u", &at(16)), 0xff80);
+    assert_eq!(memory.call_i64("i64.load32_s", &at(16)), 0x817f_ff80u32 as i32 as i64);
+    assert_eq!(memory.call_i64("i64.load32_u", &at(16)), 0x817f_ff80);
+    assert_eq!(memory.call_i64("i64.load", &at(16)), 0x0504_0302_817f_ff80);
+}
+
+#[test]
+fn narrow_stores_keep_the_low_bytes() {
+    let mut memory = Instance::new(MEMORY);
+    memory.try_call("i32.store8", &[Value::i32(0), Value::i32(0x1234)]).unwrap();
+    memory.try_call("i32.store16", &[Value::i32(2), Value::i32(0x0012_3456)]).unwrap();
+    memory.try_call("i64.store32", &[Value::i32(4), Value::i64(0x11_2233_4455)]).unwrap();
+    assert_eq!(memory.call_i64("i64.load", &at(0)), 0x2233_4455_3456_0034);
+
+    memory.try_call("i64.store8", &[Value::i32(1), Value::i64(-1)]).unwrap();
+    memory.try_call("i64.store16", &[Value::i32(6), Value::i64(0x1_beef)]).unwrap();
+    assert_eq!(memory.call_i64("i64.load", &at(0)), 0xbeef_4455_3456_ff34u64 as i64);
+    assert_eq!(memory.call("f64.round_trip", &[Value::i32(8), Value::f64(-1.5)]).as_f64(), Some(-1.5));
+}
+
+#[test]
+fn static_offsets_add_to_the_address() {
+    let mut memory = Instance::new(MEMORY);
+    assert_eq!(memory.call_i32("load_offset", &at(12)), 0x80);
+    memory.try_call("store_offset", &[Value::i32(15), Value::i32(0x7f)]).unwrap();
+    assert_eq!(memory.call_i32("i32.load8_u", &at(16)), 0x7f);
+    assert_eq!(memory.call_i32("load_high_offset", &at(0)), 0xbbaa_0000u32 as i32);
+    assert_eq!(memory.trap("load_high_offset", &at(1)), TrapKind::MemoryOutOfBounds);
+    assert_eq!(memory.trap("load_offset", &at(65532)), TrapKind::MemoryOutOfBounds);
+    // The effective address doesn't wrap around to the start of memory.
+    assert_eq!(memory.trap("load_max_offset", &at(1)), TrapKind::MemoryOutOfBounds);
+    assert_eq!(memory.trap("load_max_offset", &at(0)), TrapKind::MemoryOutOfBounds);
+}
+
+#[test]
+fn active_data_segments_are_placed_at_their_offsets() {
+    let mut memory = Instance::new(MEMORY);
+    assert_eq!(memory.call_i32("i32.load8_u", &at(15)), 0);
+    assert_eq!(memory.call_i32("i32.load8_u", &at(23)), 5);
+    assert_eq!(memory.call_i32("i32.load8_u", &at(24)), 0);
+    assert_eq!(memory.call_i32("i32.load16_u", &at(65534)), 0xbbaa);
+}
+
+#[test]
+fn out_of_bounds_data_segments_fail_instantiation() {
+    let mut imports = Imports::new();
+    let wasm = wat::parse_str(r#"(module (memory 1) (data (i32.const 65535) "\00\00"))"#).unwrap();
+    let bytecode = compile_wasm(&wasm, &imports).unwrap();
+    let mut vm = Vm::new();
+    assert!(vm.instantiate(&bytecode, &mut imports).is_err());
+}
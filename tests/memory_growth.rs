@@ -0,0 +1,69 @@
+mod common;
+
+use common::Instance;
+use weloce::{compile_wasm, Config, Imports, Value, Vm};
+
+const GROWABLE: &str = r#"(module
+    (memory 1 3)
+    (func (export "grow") (param i32) (result i32) (memory.grow (local.get 0)))
+    (func (export "size") (result i32) (memory.size))
+    (func (export "store") (param i32 i32) (i32.store8 (local.get 0) (local.get 1)))
+    (func (export "load") (param i32) (result i32) (i32.load8_u (local.get 0))))"#;
+
+const UNBOUNDED: &str = r#"(module
+    (memory 1)
+    (func (export "grow") (param i32) (result i32) (memory.grow (local.get 0)))
+    (func (export "size") (result i32) (memory.size)))"#;
+
+#[test]
+fn size_reflects_growth() {
+    let mut memory = Instance::new(GROWABLE);
+    assert_eq!(memory.call_i32("size", &[]), 1);
+    assert_eq!(memory.call_i32("grow", &[Value::i32(1)]), 1);
+    assert_eq!(memory.call_i32("size", &[]), 2);
+    assert_eq!(memory.call_i32("grow", &[Value::i32(0)]), 2);
+    assert_eq!(memory.call_i32("size", &[]), 2);
+    // New pages start zeroed and are addressable.
+    assert_eq!(memory.call_i32("load", &[Value::i32(2 * 65536 - 1)]), 0);
+}
+
+#[test]
+fn growing_past_the_declared_maximum_fails() {
+    let mut memory = Instance::new(GROWABLE);
+    memory.try_call("store", &[Value::i32(100), Value::i32(42)]).unwrap();
+    assert_eq!(memory.call_i32("grow", &[Value::i32(3)]), -1);
+    assert_eq!(memory.call_i32("grow", &[Value::i32(-1)]), -1);
+    assert_eq!(memory.call_i32("size", &[]), 1);
+    assert_eq!(memory.call_i32("load", &[Value::i32(100)]), 42);
+    assert_eq!(memory.call_i32("grow", &[Value::i32(2)]), 1);
+    assert_eq!(memory.call_i32("grow", &[Value::i32(1)]), -1);
+    assert_eq!(memory.call_i32("size", &[]), 3);
+    assert_eq!(memory.call_i32("load", &[Value::i32(100)]), 42);
+}
+
+#[test]
+fn growing_past_the_configured_cap_fails() {
+    let mut memory = Instance::with_config(UNBOUNDED, Config::new().max_memory_pages(2));
+    assert_eq!(memory.call_i32("grow", &[Value::i32(2)]), -1);
+    assert_eq!(memory.call_i32("size", &[]), 1);
+    assert_eq!(memory.call_i32("grow", &[Value::i32(1)]), 1);
+    assert_eq!(memory.call_i32("grow", &[Value::i32(1)]), -1);
+    assert_eq!(memory.call_i32("size", &[]), 2);
+
+    // The cap also applies below a declared maximum.
+    let mut memory = Instance::with_config(GROWABLE, Config::new().max_memory_pages(2));
+    assert_eq!(memory.call_i32("grow", &[Value::i32(2)]), -1);
+    assert_eq!(memory.call_i32("size", &[]), 1);
+}
+
+#[test]
+fn instantiation_fails_when_the_initial_size_exceeds_the_cap() {
+    let mut imports = Imports::new();
+    let wasm = wat::parse_str("(module (memory 3))").unwrap();
+    let bytecode = compile_wasm(&wasm, &imports).unwrap();
+    let mut vm = Vm::with_config(Config::new().max_memory_pages(2));
+    assert!(vm.instantiate(&bytecode, &mut imports).is_err());
+
+    let mut vm = Vm::with_config(Config::new().max_memory_pages(3));
+    vm.instantiate(&bytecode, &mut imports).unwrap();
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use common::Instance;
+use weloce::{Imports, Return, ValType, Value};
+
+const GUEST: &str = r#"(module
+    (import "env" "triple" (func $triple (param i32) (result i32 i64 i32)))
+    ;; Subtracting the last result from the first tells the two i32s apart.
+    (func (export "difference") (result i32) (local $last i32)
+        (call $triple (i32.const 5))
+        (local.set $last)
+        (drop)
+        (i32.sub (local.get $last)))
+    (func (export "forward") (param i32) (result i32 i64 i32) (call $triple (local.get 0))))"#;
+
+fn instance() -> Instance {
+    let mut imports = Imports::new();
+    imports.add_import(
+        "env",
+        "triple",
+        vec![ValType::I32],
+        vec![ValType::I32, ValType::I64, ValType::I32],
+        Box::new(|args| {
+            let n = args[0].as_i32().unwrap();
+            Ok(Return::Multiple(vec![Value::i32(n * 2), Value::i64(n as i64 * 100), Value::i32(n)]))
+        }),
+    );
+    Instance::with_imports(GUEST, imports)
+}
+
+#[test]
+fn guest_sees_import_results_in_declaration_order() {
+    let mut instance = instance();
+    assert_eq!(instance.call_i32("difference", &[]), 5);
+    match instance.try_call("forward", &[Value::i32(3)]).unwrap() {
+        Return::Multiple(values) => {
+            assert_eq!(values[0].as_i32(), Some(6));
+            assert_eq!(values[1].as_i64(), Some(300));
+            assert_eq!(values[2].as_i32(), Some(3));
+        }
+        result => panic!("Expected multiple values, got {:?}", result),
+    }
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use common::Instance;
+use weloce::{Trap, Value};
+
+const LOCALS: &str = r#"(module
+    (func (export "i32") (param i32 i64 f32 f64 v128) (result i32) (local.get 0))
+    (func (export "i64") (param i32 i64 f32 f64 v128) (result i64) (local.get 1))
+    (func (export "f32") (param i32 i64 f32 f64 v128) (result f32) (local.get 2))
+    (func (export "f64") (param i32 i64 f32 f64 v128) (result f64) (local.get 3))
+    (func (export "v128") (param i32 i64 f32 f64 v128) (result v128) (local.get 4))
+    ;; Parameters passed on by the guest rather than the host.
+    (func (export "forward") (param i64 v128) (result v128)
+        (call $last (i32.const 1) (local.get 0) (f32.const 2) (f64.const 3) (local.get 1)))
+    (func $last (param i32 i64 f32 f64 v128) (result v128) (local.get 4))
+    ;; Leaves non-zero values where the next frame's locals go.
+    (func $scribble (param i32) (local i64 f32 f64 v128)
+        (local.set 1 (i64.const -1))
+        (local.set 2 (f32.const -1))
+        (local.set 3 (f64.const -1))
+        (local.set 4 (v128.const i64x2 -1 -1)))
+    (func $zeroed (param i32) (result i32) (local i64 f32 f64 v128 externref funcref)
+        (i32.and
+            (i32.and
+                (i32.and (i64.eqz (local.get 1)) (f32.eq (local.get 2) (f32.const 0)))
+                (i32.and (f64.eq (local.get 3) (f64.const 0)) (i32.eqz (v128.any_true (local.get 4)))))
+            (i32.and (ref.is_null (local.get 5)) (ref.is_null (local.get 6)))))
+    (func (export "locals_start_at_zero") (result i32)
+        (call $scribble (i32.const 1))
+        (call $zeroed (i32.const 1))))"#;
+
+fn mixed() -> [Value; 5] {
+    [Value::i32(-7), Value::i64(i64::MIN + 1), Value::f32(1.5), Value::f64(-2.25), Value::v128(u128::MAX - 1)]
+}
+
+#[test]
+fn mixed_parameters_land_in_their_local_slots() {
+    let mut locals = Instance::new(LOCALS);
+    assert_eq!(locals.call_i32("i32", &mixed()), -7);
+    assert_eq!(locals.call_i64("i64", &mixed()), i64::MIN + 1);
+    assert_eq!(locals.call("f32", &mixed()).as_f32(), Some(1.5));
+    assert_eq!(locals.call("f64", &mixed()).as_f64(), Some(-2.25));
+    assert_eq!(locals.call_v128("v128", &mixed()), u128::MAX - 1);
+    assert_eq!(locals.call_v128("forward", &[Value::i64(-1), Value::v128(1 << 127)]), 1 << 127);
+}
+
+#[test]
+fn non_parameter_locals_start_at_zero() {
+    let mut locals = Instance::new(LOCALS);
+    assert_eq!(locals.call_i32("locals_start_at_zero", &[]), 1);
+    assert_eq!(locals.call_i32("locals_start_at_zero", &[]), 1);
+}
+
+#[test]
+fn wrong_arguments_are_rejected_before_running() {
+    let mut locals = Instance::new(LOCALS);
+    let error = locals.try_call("i32", &mixed()[..4]).unwrap_err();
+    assert_eq!(error.to_string(), "Function i32 expects 5 arguments but 4 were given");
+    assert!(locals.try_call("locals_start_at_zero", &[Value::i32(1)]).is_err());
+
+    let mut args = mixed();
+    args[2] = Value::f64(1.5);
+    let error = locals.try_call("i32", &args).unwrap_err();
+    assert_eq!(error.to_string(), "Argument 2 of function i32 has type F64 but F32 was expected");
+    assert!(error.downcast_ref::<Trap>().is_none());
+    assert_eq!(locals.call_i32("i32", &mixed()), -7);
+}
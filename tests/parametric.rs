@@ -0,0 +1,78 @@
+mod common;
+
+use common::Instance;
+use weloce::Value;
+
+const OPS: &str = r#"(module
+    (elem declare func $callee)
+    (func $callee)
+    (func (export "drop") (param i32 i32) (result i32)
+        (local.get 0)
+        (local.get 1)
+        (drop))
+    (func (export "drop_v128") (param i32 v128) (result i32)
+        (local.get 0)
+        (local.get 1)
+        (drop))
+    (func (export "select") (param i32 i32 i32) (result i32) (select (local.get 0) (local.get 1) (local.get 2)))
+    (func (export "select_i64") (param i64 i64 i32) (result i64) (select (local.get 0) (local.get 1) (local.get 2)))
+    (func (export "select_f64") (param f64 f64 i32) (result f64) (select (local.get 0) (local.get 1) (local.get 2)))
+    (func (export "select_v128") (param v128 v128 i32) (result v128)
+        (select (result v128) (local.get 0) (local.get 1) (local.get 2)))
+    (func (export "select_funcref") (param i32) (result funcref)
+        (select (result funcref) (ref.func $callee) (ref.null func) (local.get 0)))
+    (func (export "select_externref") (param externref externref i32) (result externref)
+        (select (result externref) (local.get 0) (local.get 1) (local.get 2)))
+    (func (export "tee") (param i32) (result i32) (local $copy i32)
+        (i32.add (local.tee $copy (local.get 0)) (local.get $copy)))
+    (func (export "tee_v128") (param v128) (result v128) (local $copy v128)
+        (i64x2.add (local.tee $copy (local.get 0)) (local.get $copy))))"#;
+
+#[test]
+fn drop_discards_the_top_value() {
+    let mut ops = Instance::new(OPS);
+    assert_eq!(ops.call_i32("drop", &[Value::i32(1), Value::i32(2)]), 1);
+    assert_eq!(ops.call_i32("drop_v128", &[Value::i32(1), Value::v128(u128::MAX)]), 1);
+}
+
+#[test]
+fn select_picks_the_first_operand_unless_the_condition_is_zero() {
+    let mut ops = Instance::new(OPS);
+    for (condition, expected) in [(1, 10), (-1, 10), (0, 20)] {
+        assert_eq!(ops.call_i32("select", &[Value::i32(10), Value::i32(20), Value::i32(condition)]), expected);
+    }
+    assert_eq!(ops.call_i64("select_i64", &[Value::i64(-1), Value::i64(i64::MIN), Value::i32(0)]), i64::MIN);
+    assert_eq!(ops.call_i64("select_i64", &[Value::i64(-1), Value::i64(i64::MIN), Value::i32(2)]), -1);
+    assert_eq!(ops.call("select_f64", &[Value::f64(1.5), Value::f64(-0.0), Value::i32(1)]).as_f64(), Some(1.5));
+    assert_eq!(ops.call_f64_bits("select_f64", &[Value::f64(1.5), Value::f64(-0.0), Value::i32(0)]), (-0.0f64).to_bits());
+}
+
+#[test]
+fn typed_select_picks_v128_and_references() {
+    let mut ops = Instance::new(OPS);
+    let (a, b) = (Value::v128(u128::MAX), Value::v128(1 << 100));
+    assert_eq!(ops.call_v128("select_v128", &[a.clone(), b.clone(), Value::i32(1)]), u128::MAX);
+    assert_eq!(ops.call_v128("select_v128", &[a, b, Value::i32(0)]), 1 << 100);
+
+    assert_eq!(ops.call("select_funcref", &[Value::i32(1)]).as_func_ref(), Some(Some(0)));
+    assert_eq!(ops.call("select_funcref", &[Value::i32(0)]).as_func_ref(), Some(None));
+
+    let first = ops.vm.new_extern(Box::new(1)).unwrap();
+    let second = ops.vm.new_extern(Box::new(2)).unwrap();
+    for (condition, expected) in [(1, &first), (0, &second)] {
+        let selected = ops.call("select_externref", &[first.clone(), second.clone(), Value::i32(condition)]);
+        assert_eq!(selected.as_extern_ref(), expected.as_extern_ref());
+        ops.vm.release_extern(&selected);
+    }
+    let selected = ops.call("select_externref", &[first.clone(), Value::null_extern_ref(), Value::i32(0)]);
+    assert_eq!(selected.as_extern_ref(), Some(None));
+}
+
+#[test]
+fn tee_sets_the_local_and_keeps_the_value() {
+    let mut ops = Instance::new(OPS);
+    assert_eq!(ops.call_i32("tee", &[Value::i32(21)]), 42);
+    assert_eq!(ops.call_i32("tee", &[Value::i32(-3)]), -6);
+    let lanes = 5u128 | (7u128 << 64);
+    assert_eq!(ops.call_v128("tee_v128", &[Value::v128(lanes)]), 10 | (14 << 64));
+}
@@ -0,0 +1,65 @@
+mod common;
+
+use common::Instance;
+use weloce::Value;
+
+const REFS: &str = r#"(module
+    (type $number (func (result i32)))
+    (table $funcs 1 funcref)
+    (table $externs 1 externref)
+    (elem declare func $one $two)
+    (func $one (result i32) (i32.const 1))
+    (func $two (result i32) (i32.const 2))
+    (func (export "func_is_null") (param funcref) (result i32) (ref.is_null (local.get 0)))
+    (func (export "extern_is_null") (param externref) (result i32) (ref.is_null (local.get 0)))
+    (func (export "local_ref_is_null") (result i32) (ref.is_null (ref.func $one)))
+    (func (export "choose") (param i32)
+        (table.set $funcs (i32.const 0)
+            (select (result funcref) (ref.func $two) (ref.func $one) (local.get 0))))
+    (func (export "install") (param funcref) (table.set $funcs (i32.const 0) (local.get 0)))
+    (func (export "call") (result i32) (call_indirect $funcs (type $number) (i32.const 0)))
+    (func (export "identity") (param externref) (result externref) (local.get 0))
+    (func (export "stash") (param externref) (table.set $externs (i32.const 0) (local.get 0)))
+    (func (export "fetch") (result externref) (table.get $externs (i32.const 0))))"#;
+
+#[test]
+fn is_null_tells_null_references_apart() {
+    let mut refs = Instance::new(REFS);
+    assert_eq!(refs.call_i32("func_is_null", &[Value::func_ref(None)]), 1);
+    assert_eq!(refs.call_i32("func_is_null", &[Value::func_ref(Some(0))]), 0);
+    assert_eq!(refs.call_i32("local_ref_is_null", &[]), 0);
+    assert_eq!(refs.call_i32("extern_is_null", &[Value::null_extern_ref()]), 1);
+    let object = refs.vm.new_extern(Box::new("object")).unwrap();
+    assert_eq!(refs.call_i32("extern_is_null", &[object]), 0);
+}
+
+#[test]
+fn ref_func_feeds_call_indirect_through_a_table() {
+    let mut refs = Instance::new(REFS);
+    refs.try_call("choose", &[Value::i32(1)]).unwrap();
+    assert_eq!(refs.call_i32("call", &[]), 2);
+    refs.try_call("choose", &[Value::i32(0)]).unwrap();
+    assert_eq!(refs.call_i32("call", &[]), 1);
+    // Function references from the host index the module's functions too.
+    refs.try_call("install", &[Value::func_ref(Some(1))]).unwrap();
+    assert_eq!(refs.call_i32("call", &[]), 2);
+}
+
+#[test]
+fn externrefs_pass_through_the_guest_and_back() {
+    let mut refs = Instance::new(REFS);
+    let object = refs.vm.new_extern(Box::new(String::from("host object"))).unwrap();
+    let returned = refs.call("identity", std::slice::from_ref(&object));
+    assert_eq!(returned.as_extern_ref(), object.as_extern_ref());
+    refs.vm.release_extern(&returned);
+
+    refs.try_call("stash", std::slice::from_ref(&object)).unwrap();
+    refs.vm.release_extern(&object);
+    let fetched = refs.call("fetch", &[]);
+    let text = refs.vm.extern_object(&fetched).and_then(|object| object.downcast_ref::<String>());
+    assert_eq!(text.map(String::as_str), Some("host object"));
+    refs.vm.release_extern(&fetched);
+
+    let returned = refs.call("identity", &[Value::null_extern_ref()]);
+    assert_eq!(returned.as_extern_ref(), Some(None));
+}
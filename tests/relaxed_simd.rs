@@ -0,0 +1,113 @@
+mod common;
+
+use common::Instance;
+use weloce::{Config, Value};
+
+const OPS: &str = r#"(module
+    (func (export "f32x4.madd") (param v128 v128 v128) (result v128)
+        (f32x4.relaxed_madd (local.get 0) (local.get 1) (local.get 2)))
+    (func (export "f32x4.nmadd") (param v128 v128 v128) (result v128)
+        (f32x4.relaxed_nmadd (local.get 0) (local.get 1) (local.get 2)))
+    (func (export "f64x2.madd") (param v128 v128 v128) (result v128)
+        (f64x2.relaxed_madd (local.get 0) (local.get 1) (local.get 2)))
+    (func (export "f32x4.min") (param v128 v128) (result v128) (f32x4.relaxed_min (local.get 0) (local.get 1)))
+    (func (export "f32x4.max") (param v128 v128) (result v128) (f32x4.relaxed_max (local.get 0) (local.get 1)))
+    (func (export "f64x2.min") (param v128 v128) (result v128) (f64x2.relaxed_min (local.get 0) (local.get 1)))
+    (func (export "dot") (param v128 v128) (result v128) (i16x8.relaxed_dot_i8x16_i7x16_s (local.get 0) (local.get 1)))
+    (func (export "dot_add") (param v128 v128 v128) (result v128)
+        (i32x4.relaxed_dot_i8x16_i7x16_add_s (local.get 0) (local.get 1) (local.get 2))))"#;
+
+fn instance(deterministic: bool) -> Instance {
+    Instance::with_config(OPS, Config::new().relaxed_simd_deterministic(deterministic))
+}
+
+fn v128(ops: &mut Instance, name: &str, args: &[u128]) -> u128 {
+    let args: Vec<Value> = args.iter().map(|arg| Value::v128(*arg)).collect();
+    ops.call_v128(name, &args)
+}
+
+fn i32s(lanes: [i32; 4]) -> u128 {
+    f32_bits(lanes.map(|lane| lane as u32))
+}
+
+fn f32_bits(lanes: [u32; 4]) -> u128 {
+    lanes.iter().rev().fold(0, |vector, lane| vector << 32 | *lane as u128)
+}
+
+fn f32s(value: f32) -> u128 {
+    f32_bits([value.to_bits(); 4])
+}
+
+fn f64_bits(lanes: [u64; 2]) -> u128 {
+    (lanes[1] as u128) << 64 | lanes[0] as u128
+}
+
+fn f64s(value: f64) -> u128 {
+    f64_bits([value.to_bits(); 2])
+}
+
+// 1 + 2^-23 squared is 1 + 2^-22 + 2^-46, whose last term only survives if
+// the product isn't rounded before the addition.
+const F32_ONE_ULP: f32 = 1.000_000_1;
+const F32_TWO_ULPS: f32 = 1.000_000_2;
+const F64_ONE_ULP: f64 = 1.000_000_000_000_000_2;
+const F64_TWO_ULPS: f64 = 1.000_000_000_000_000_4;
+
+#[test]
+fn madd_rounds_once_when_deterministic() {
+    let mut ops = instance(true);
+    let product = f32s(F32_ONE_ULP);
+    assert_eq!(v128(&mut ops, "f32x4.madd", &[product, product, f32s(-F32_TWO_ULPS)]), f32s(2f32.powi(-46)));
+    assert_eq!(v128(&mut ops, "f32x4.nmadd", &[product, product, f32s(F32_TWO_ULPS)]), f32s(-(2f32.powi(-46))));
+    let product = f64s(F64_ONE_ULP);
+    assert_eq!(v128(&mut ops, "f64x2.madd", &[product, product, f64s(-F64_TWO_ULPS)]), f64s(2f64.powi(-104)));
+}
+
+#[test]
+fn madd_rounds_twice_otherwise() {
+    let mut ops = instance(false);
+    let product = f32s(F32_ONE_ULP);
+    assert_eq!(v128(&mut ops, "f32x4.madd", &[product, product, f32s(-F32_TWO_ULPS)]), f32s(0.0));
+    assert_eq!(v128(&mut ops, "f32x4.nmadd", &[product, product, f32s(F32_TWO_ULPS)]), f32s(0.0));
+    let product = f64s(F64_ONE_ULP);
+    assert_eq!(v128(&mut ops, "f64x2.madd", &[product, product, f64s(-F64_TWO_ULPS)]), f64s(0.0));
+}
+
+#[test]
+fn min_and_max_follow_the_wasm_rules_when_deterministic() {
+    let mut ops = instance(true);
+    let a = f32_bits([0xffc0_0001, 1f32.to_bits(), 0x0000_0000, 0x8000_0000]);
+    let b = f32_bits([1f32.to_bits(), 0x7f80_0001, 0x8000_0000, 0x0000_0000]);
+    assert_eq!(v128(&mut ops, "f32x4.min", &[a, b]), f32_bits([0x7fc0_0000, 0x7fc0_0000, 0x8000_0000, 0x8000_0000]));
+    assert_eq!(v128(&mut ops, "f32x4.max", &[a, b]), f32_bits([0x7fc0_0000, 0x7fc0_0000, 0x0000_0000, 0x0000_0000]));
+    let a = f64_bits([f64::NAN.to_bits(), 0x8000_0000_0000_0000]);
+    let b = f64_bits([2f64.to_bits(), 0]);
+    assert_eq!(v128(&mut ops, "f64x2.min", &[a, b]), f64_bits([0x7ff8_0000_0000_0000, 0x8000_0000_0000_0000]));
+}
+
+// Otherwise the second operand wins whenever the comparison fails, as with
+// x86's `minps` and `maxps`.
+#[test]
+fn min_and_max_pick_the_second_operand_otherwise() {
+    let mut ops = instance(false);
+    let a = f32_bits([0xffc0_0001, 1f32.to_bits(), 0x0000_0000, 0x8000_0000]);
+    let b = f32_bits([1f32.to_bits(), 0x7f80_0001, 0x8000_0000, 0x0000_0000]);
+    assert_eq!(v128(&mut ops, "f32x4.min", &[a, b]), f32_bits([1f32.to_bits(), 0x7f80_0001, 0x8000_0000, 0x0000_0000]));
+    assert_eq!(v128(&mut ops, "f32x4.max", &[a, b]), f32_bits([1f32.to_bits(), 0x7f80_0001, 0x8000_0000, 0x0000_0000]));
+    let a = f64_bits([f64::NAN.to_bits(), 0x8000_0000_0000_0000]);
+    let b = f64_bits([2f64.to_bits(), 0]);
+    assert_eq!(v128(&mut ops, "f64x2.min", &[a, b]), f64_bits([2f64.to_bits(), 0]));
+}
+
+// -128 is outside the 7-bit range of the second operand. Both products of a
+// pair are then 2^14, and their sum wraps in the 16-bit intermediate lane.
+#[test]
+fn dot_products_wrap_on_out_of_range_pairs() {
+    for deterministic in [true, false] {
+        let mut ops = instance(deterministic);
+        let min = u128::from_le_bytes([0x80; 16]);
+        assert_eq!(v128(&mut ops, "dot", &[min, min]), u128::from_le_bytes(i16::MIN.to_le_bytes().repeat(8).try_into().unwrap()));
+        let c = i32s([0, 1, 65536, -1]);
+        assert_eq!(v128(&mut ops, "dot_add", &[min, min, c]), i32s([-65536, -65535, 0, -65537]));
+    }
+}
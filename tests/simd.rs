@@ -0,0 +1,151 @@
+mod common;
+
+use common::Instance;
+use weloce::{TrapKind, Value};
+
+const OPS: &str = r#"(module
+    (memory 1)
+    (func (export "shuffle") (param v128 v128) (result v128)
+        (i8x16.shuffle 0 16 1 17 15 31 30 14 2 2 2 2 16 16 16 16 (local.get 0) (local.get 1)))
+    (func (export "swizzle") (param v128 v128) (result v128) (i8x16.swizzle (local.get 0) (local.get 1)))
+    (func (export "narrow_s") (param v128 v128) (result v128) (i8x16.narrow_i16x8_s (local.get 0) (local.get 1)))
+    (func (export "narrow_u") (param v128 v128) (result v128) (i8x16.narrow_i16x8_u (local.get 0) (local.get 1)))
+    (func (export "add_sat_s") (param v128 v128) (result v128) (i8x16.add_sat_s (local.get 0) (local.get 1)))
+    (func (export "add_sat_u") (param v128 v128) (result v128) (i8x16.add_sat_u (local.get 0) (local.get 1)))
+    (func (export "sub_sat_u") (param v128 v128) (result v128) (i16x8.sub_sat_u (local.get 0) (local.get 1)))
+    (func (export "f32x4.min") (param v128 v128) (result v128) (f32x4.min (local.get 0) (local.get 1)))
+    (func (export "f32x4.max") (param v128 v128) (result v128) (f32x4.max (local.get 0) (local.get 1)))
+    (func (export "f32x4.add") (param v128 v128) (result v128) (f32x4.add (local.get 0) (local.get 1)))
+    (func (export "f64x2.sqrt") (param v128) (result v128) (f64x2.sqrt (local.get 0)))
+    (func (export "trunc_sat_s") (param v128) (result v128) (i32x4.trunc_sat_f32x4_s (local.get 0)))
+    (func (export "trunc_sat_u") (param v128) (result v128) (i32x4.trunc_sat_f32x4_u (local.get 0)))
+    (func (export "trunc_sat_zero") (param v128) (result v128) (i32x4.trunc_sat_f64x2_s_zero (local.get 0)))
+    (func (export "bitmask") (param v128) (result i32) (i8x16.bitmask (local.get 0)))
+    (func (export "all_true") (param v128) (result i32) (i32x4.all_true (local.get 0)))
+    (func (export "load32_lane") (param i32) (result v128)
+        (v128.load32_lane 1 (local.get 0) (v128.const i64x2 0 0)))
+    (func (export "store64_lane") (param i32)
+        (v128.store64_lane 1 (local.get 0) (v128.const i64x2 1 2)))
+    (func (export "load") (param i32) (result v128) (v128.load (local.get 0))))"#;
+
+// Calls with v128 arguments, which is what most of the operations here take.
+fn v128(ops: &mut Instance, name: &str, args: &[u128]) -> u128 {
+    let args: Vec<Value> = args.iter().map(|arg| Value::v128(*arg)).collect();
+    ops.call_v128(name, &args)
+}
+
+fn i8s(lanes: [i8; 16]) -> u128 {
+    u128::from_le_bytes(lanes.map(|lane| lane as u8))
+}
+
+fn u8s(lanes: [u8; 16]) -> u128 {
+    u128::from_le_bytes(lanes)
+}
+
+fn i16s(lanes: [i16; 8]) -> u128 {
+    lanes.iter().rev().fold(0, |vector, lane| vector << 16 | *lane as u16 as u128)
+}
+
+fn i32s(lanes: [i32; 4]) -> u128 {
+    lanes.iter().rev().fold(0, |vector, lane| vector << 32 | *lane as u32 as u128)
+}
+
+fn f32_bits(lanes: [u32; 4]) -> u128 {
+    lanes.iter().rev().fold(0, |vector, lane| vector << 32 | *lane as u128)
+}
+
+fn f32s(lanes: [f32; 4]) -> u128 {
+    f32_bits(lanes.map(f32::to_bits))
+}
+
+fn f64s(lanes: [f64; 2]) -> u128 {
+    (lanes[1].to_bits() as u128) << 64 | lanes[0].to_bits() as u128
+}
+
+#[test]
+fn shuffle_indexes_both_operands() {
+    let mut ops = Instance::new(OPS);
+    let a = u8s([0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
+    let b = u8s([100, 101, 102, 103, 104, 105, 106, 107, 108, 109, 110, 111, 112, 113, 114, 115]);
+    assert_eq!(v128(&mut ops, "shuffle", &[a, b]), u8s([0, 100, 1, 101, 15, 115, 114, 14, 2, 2, 2, 2, 100, 100, 100, 100]));
+}
+
+#[test]
+fn swizzle_zeroes_out_of_range_lanes() {
+    let mut ops = Instance::new(OPS);
+    let a = u8s([10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25]);
+    let indices = u8s([15, 0, 16, 255, 128, 1, 17, 3, 0, 0, 0, 0, 0, 0, 0, 31]);
+    assert_eq!(v128(&mut ops, "swizzle", &[a, indices]), u8s([25, 10, 0, 0, 0, 11, 0, 13, 10, 10, 10, 10, 10, 10, 10, 0]));
+}
+
+#[test]
+fn narrowing_saturates() {
+    let mut ops = Instance::new(OPS);
+    let a = i16s([300, -300, 127, -128, 128, -129, 0, -1]);
+    let b = i16s([i16::MAX, i16::MIN, 255, 256, 1, 2, 3, 4]);
+    assert_eq!(v128(&mut ops, "narrow_s", &[a, b]), i8s([127, -128, 127, -128, 127, -128, 0, -1, 127, -128, 127, 127, 1, 2, 3, 4]));
+    assert_eq!(v128(&mut ops, "narrow_u", &[a, b]), u8s([255, 0, 127, 0, 128, 0, 0, 0, 255, 0, 255, 255, 1, 2, 3, 4]));
+}
+
+#[test]
+fn saturating_arithmetic_clamps() {
+    let mut ops = Instance::new(OPS);
+    let a = i8s([100, -100, 127, -128, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    let b = i8s([100, -100, 1, -1, -1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(v128(&mut ops, "add_sat_s", &[a, b]), i8s([127, -128, 127, -128, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]));
+    let a = u8s([200, 255, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    let b = u8s([100, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(v128(&mut ops, "add_sat_u", &[a, b]), u8s([255, 255, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]));
+    let a = i16s([5, -1, 0, 100, 0, 0, 0, 0]);
+    let b = i16s([10, 1, 1, 100, 0, 0, 0, 0]);
+    assert_eq!(v128(&mut ops, "sub_sat_u", &[a, b]), i16s([0, -2, 0, 0, 0, 0, 0, 0]));
+}
+
+#[test]
+fn float_min_and_max_propagate_nan_and_order_zeros() {
+    let mut ops = Instance::new(OPS);
+    let a = f32_bits([0xffc0_0001, 0x0000_0000, 0x8000_0000, 1f32.to_bits()]);
+    let b = f32_bits([1f32.to_bits(), 0x8000_0000, 0x0000_0000, 0x7f80_0001]);
+    assert_eq!(v128(&mut ops, "f32x4.min", &[a, b]), f32_bits([0x7fc0_0000, 0x8000_0000, 0x8000_0000, 0x7fc0_0000]));
+    assert_eq!(v128(&mut ops, "f32x4.max", &[a, b]), f32_bits([0x7fc0_0000, 0x0000_0000, 0x0000_0000, 0x7fc0_0000]));
+}
+
+#[test]
+fn float_arithmetic_produces_canonical_nans() {
+    let mut ops = Instance::new(OPS);
+    let a = f32_bits([0xffc0_1234, 0x7f80_0001, 0, 0x7f80_0000]);
+    let b = f32s([1.0, 1.0, 0.0, f32::NEG_INFINITY]);
+    assert_eq!(v128(&mut ops, "f32x4.add", &[a, b]), f32_bits([0x7fc0_0000, 0x7fc0_0000, 0, 0x7fc0_0000]));
+    assert_eq!(v128(&mut ops, "f64x2.sqrt", &[f64s([-1.0, 4.0])]), f64s([f64::from_bits(0x7ff8_0000_0000_0000), 2.0]));
+}
+
+#[test]
+fn trunc_sat_clamps_edge_values() {
+    let mut ops = Instance::new(OPS);
+    let a = f32s([f32::NAN, 3e9, -3e9, -1.5]);
+    assert_eq!(v128(&mut ops, "trunc_sat_s", &[a]), i32s([0, i32::MAX, i32::MIN, -1]));
+    let a = f32s([-1.0, 5e9, f32::INFINITY, 4294967040.0]);
+    assert_eq!(v128(&mut ops, "trunc_sat_u", &[a]), i32s([0, -1, -1, 4294967040u32 as i32]));
+    assert_eq!(v128(&mut ops, "trunc_sat_zero", &[f64s([f64::NEG_INFINITY, 2147483647.9])]), i32s([i32::MIN, i32::MAX, 0, 0]));
+}
+
+#[test]
+fn bitmask_and_all_true() {
+    let mut ops = Instance::new(OPS);
+    assert_eq!(ops.call_i32("bitmask", &[Value::v128(i8s([-1, 0, -128, 127, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -2]))]), 0b1000_0000_0000_0101);
+    assert_eq!(ops.call_i32("bitmask", &[Value::v128(0)]), 0);
+    assert_eq!(ops.call_i32("all_true", &[Value::v128(i32s([1, -1, i32::MIN, 0x100]))]), 1);
+    assert_eq!(ops.call_i32("all_true", &[Value::v128(i32s([1, -1, 0, 0x100]))]), 0);
+}
+
+#[test]
+fn lane_accesses_trap_out_of_bounds() {
+    let mut ops = Instance::new(OPS);
+    assert!(ops.try_call("load32_lane", &[Value::i32(65532)]).is_ok());
+    assert_eq!(ops.trap("load32_lane", &[Value::i32(65533)]), TrapKind::MemoryOutOfBounds);
+    assert_eq!(ops.trap("load32_lane", &[Value::i32(-1)]), TrapKind::MemoryOutOfBounds);
+    assert!(ops.try_call("store64_lane", &[Value::i32(65528)]).is_ok());
+    assert_eq!(ops.trap("store64_lane", &[Value::i32(65529)]), TrapKind::MemoryOutOfBounds);
+    assert!(ops.try_call("load", &[Value::i32(65520)]).is_ok());
+    assert_eq!(ops.trap("load", &[Value::i32(65521)]), TrapKind::MemoryOutOfBounds);
+}
@@ -0,0 +1,33 @@
+mod common;
+
+use common::Instance;
+use weloce::{compile_wasm, Imports, Trap, TrapKind, Vm};
+
+#[test]
+fn start_function_effects_are_visible_to_the_first_call() {
+    let mut instance = Instance::new(
+        r#"(module
+            (memory 1)
+            (global $started (mut i32) (i32.const 0))
+            (func $start
+                (global.set $started (i32.add (global.get $started) (i32.const 1)))
+                (i32.store (i32.const 8) (i32.const 42)))
+            (start $start)
+            (func (export "started") (result i32) (global.get $started))
+            (func (export "stored") (result i32) (i32.load (i32.const 8))))"#,
+    );
+    assert_eq!(instance.call_i32("started", &[]), 1);
+    assert_eq!(instance.call_i32("stored", &[]), 42);
+    assert_eq!(instance.call_i32("started", &[]), 1);
+}
+
+#[test]
+fn trapping_start_function_fails_instantiation() {
+    let mut imports = Imports::new();
+    let wasm = wat::parse_str("(module (func $start (unreachable)) (start $start))").unwrap();
+    let bytecode = compile_wasm(&wasm, &imports).unwrap();
+    let mut vm = Vm::new();
+    let error = vm.instantiate(&bytecode, &mut imports).unwrap_err();
+    assert_eq!(error.to_string(), "Start function failed");
+    assert_eq!(error.downcast_ref::<Trap>().map(Trap::kind), Some(TrapKind::Unreachable));
+}
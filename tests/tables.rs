@@ -0,0 +1,106 @@
+mod common;
+
+use common::Instance;
+use weloce::{TrapKind, Value};
+
+const TABLE: &str = r#"(module
+    (type $number (func (result i32)))
+    (table $table 3 5 funcref)
+    (elem (table $table) (i32.const 0) func $one $two $three)
+    (elem $passive func $one $two $three)
+    (func $one (result i32) (i32.const 1))
+    (func $two (result i32) (i32.const 2))
+    (func $three (result i32) (i32.const 3))
+    ;; Which function an entry holds, by calling it.
+    (func (export "entry") (param i32) (result i32) (call_indirect $table (type $number) (local.get 0)))
+    (func (export "is_null") (param i32) (result i32) (ref.is_null (table.get $table (local.get 0))))
+    (func (export "set") (param i32) (table.set $table (local.get 0) (ref.func $two)))
+    (func (export "clear") (param i32) (table.set $table (local.get 0) (ref.null func)))
+    (func (export "size") (result i32) (table.size $table))
+    (func (export "grow") (param i32) (result i32) (table.grow $table (ref.null func) (local.get 0)))
+    (func (export "fill") (param i32 i32) (table.fill $table (local.get 0) (ref.func $three) (local.get 1)))
+    (func (export "copy") (param i32 i32 i32) (table.copy $table $table (local.get 0) (local.get 1) (local.get 2)))
+    (func (export "init") (param i32 i32 i32) (table.init $table $passive (local.get 0) (local.get 1) (local.get 2)))
+    (func (export "drop") (elem.drop $passive)))"#;
+
+fn entries(table: &mut Instance) -> Vec<i32> {
+    (0..3).map(|index| table.call_i32("entry", &[Value::i32(index)])).collect()
+}
+
+fn args(destination: i32, source: i32, len: i32) -> [Value; 3] {
+    [Value::i32(destination), Value::i32(source), Value::i32(len)]
+}
+
+#[test]
+fn get_and_set_within_bounds() {
+    let mut table = Instance::new(TABLE);
+    assert_eq!(entries(&mut table), [1, 2, 3]);
+    assert_eq!(table.call_i32("is_null", &[Value::i32(0)]), 0);
+    table.try_call("set", &[Value::i32(0)]).unwrap();
+    table.try_call("clear", &[Value::i32(1)]).unwrap();
+    assert_eq!(table.call_i32("entry", &[Value::i32(0)]), 2);
+    assert_eq!(table.call_i32("is_null", &[Value::i32(1)]), 1);
+}
+
+#[test]
+fn get_and_set_out_of_bounds_trap() {
+    let mut table = Instance::new(TABLE);
+    assert_eq!(table.trap("is_null", &[Value::i32(3)]), TrapKind::TableOutOfBounds);
+    assert_eq!(table.trap("is_null", &[Value::i32(-1)]), TrapKind::TableOutOfBounds);
+    assert_eq!(table.trap("set", &[Value::i32(3)]), TrapKind::TableOutOfBounds);
+    assert_eq!(table.trap("clear", &[Value::i32(-1)]), TrapKind::TableOutOfBounds);
+}
+
+#[test]
+fn grow_returns_the_old_size_up_to_the_maximum() {
+    let mut table = Instance::new(TABLE);
+    assert_eq!(table.call_i32("grow", &[Value::i32(1)]), 3);
+    assert_eq!(table.call_i32("size", &[]), 4);
+    assert_eq!(table.call_i32("is_null", &[Value::i32(3)]), 1);
+    assert_eq!(table.call_i32("grow", &[Value::i32(2)]), -1);
+    assert_eq!(table.call_i32("size", &[]), 4);
+    assert_eq!(table.call_i32("grow", &[Value::i32(1)]), 4);
+    assert_eq!(table.call_i32("grow", &[Value::i32(0)]), 5);
+    assert_eq!(table.call_i32("grow", &[Value::i32(1)]), -1);
+    assert_eq!(table.call_i32("size", &[]), 5);
+}
+
+#[test]
+fn fill_sets_a_range_of_entries() {
+    let mut table = Instance::new(TABLE);
+    table.try_call("fill", &[Value::i32(1), Value::i32(2)]).unwrap();
+    assert_eq!(entries(&mut table), [1, 3, 3]);
+    assert_eq!(table.trap("fill", &[Value::i32(2), Value::i32(2)]), TrapKind::TableOutOfBounds);
+    table.try_call("fill", &[Value::i32(3), Value::i32(0)]).unwrap();
+    assert_eq!(entries(&mut table), [1, 3, 3]);
+}
+
+#[test]
+fn copy_handles_overlap_in_both_directions() {
+    let mut table = Instance::new(TABLE);
+    table.try_call("copy", &args(1, 0, 2)).unwrap();
+    assert_eq!(entries(&mut table), [1, 1, 2]);
+
+    let mut table = Instance::new(TABLE);
+    table.try_call("copy", &args(0, 1, 2)).unwrap();
+    assert_eq!(entries(&mut table), [2, 3, 3]);
+    assert_eq!(table.trap("copy", &args(2, 0, 2)), TrapKind::TableOutOfBounds);
+}
+
+#[test]
+fn init_copies_part_of_a_passive_segment() {
+    let mut table = Instance::new(TABLE);
+    table.try_call("init", &args(0, 1, 2)).unwrap();
+    assert_eq!(entries(&mut table), [2, 3, 3]);
+    assert_eq!(table.trap("init", &args(0, 2, 2)), TrapKind::TableOutOfBounds);
+    assert_eq!(table.trap("init", &args(2, 0, 2)), TrapKind::TableOutOfBounds);
+}
+
+#[test]
+fn init_after_drop_traps() {
+    let mut table = Instance::new(TABLE);
+    table.try_call("drop", &[]).unwrap();
+    assert_eq!(table.trap("init", &args(0, 0, 1)), TrapKind::TableOutOfBounds);
+    table.try_call("init", &args(0, 0, 0)).unwrap();
+    assert_eq!(entries(&mut table), [1, 2, 3]);
+}
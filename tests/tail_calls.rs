@@ -0,0 +1,79 @@
+mod common;
+
+use common::{call, instantiate};
+use weloce::{Imports, Return, ValType, Value};
+
+#[test]
+fn millions_of_self_tail_calls() {
+    let mut imports = Imports::new();
+    let (bytecode, mut vm) = instantiate(
+        r#"(module
+            (func $sum (export "sum") (param i64 i64) (result i64)
+                (if (result i64) (i64.eqz (local.get 0))
+                    (then (local.get 1))
+                    (else (return_call $sum
+                        (i64.sub (local.get 0) (i64.const 1))
+                        (i64.add (local.get 1) (local.get 0)))))))"#,
+        &mut imports,
+    );
+    let n = 2_000_000i64;
+    let result = call(&mut vm, &bytecode, "sum", &[Value::i64(n), Value::i64(0)], &mut imports);
+    assert_eq!(result.as_i64(), Some(n * (n + 1) / 2));
+}
+
+#[test]
+fn millions_of_mutual_tail_calls() {
+    let mut imports = Imports::new();
+    let (bytecode, mut vm) = instantiate(
+        r#"(module
+            (func $is_even (export "is_even") (param i32) (result i32)
+                (if (result i32) (i32.eqz (local.get 0))
+                    (then (i32.const 1))
+                    (else (return_call $is_odd (i32.sub (local.get 0) (i32.const 1))))))
+            (func $is_odd (export "is_odd") (param i32) (result i32)
+                (if (result i32) (i32.eqz (local.get 0))
+                    (then (i32.const 0))
+                    (else (return_call $is_even (i32.sub (local.get 0) (i32.const 1)))))))"#,
+        &mut imports,
+    );
+    let result = call(&mut vm, &bytecode, "is_even", &[Value::i32(1_000_000)], &mut imports);
+    assert_eq!(result.as_i32(), Some(1));
+    let result = call(&mut vm, &bytecode, "is_odd", &[Value::i32(1_000_001)], &mut imports);
+    assert_eq!(result.as_i32(), Some(1));
+}
+
+#[test]
+fn millions_of_indirect_tail_calls_ending_in_an_import() {
+    let mut imports = Imports::new();
+    imports.add_import("env", "done", vec![ValType::I32, ValType::I32], vec![ValType::I32], Box::new(|args| {
+        Ok(Return::Single(Value::i32(args[0].as_i32().unwrap() * 1000 + args[1].as_i32().unwrap())))
+    }));
+    // Each step leaves extra values on the stack and inside open blocks, which
+    // the tail call has to discard.
+    let (bytecode, mut vm) = instantiate(
+        r#"(module
+            (import "env" "done" (func $done (param i32 i32) (result i32)))
+            (type $step (func (param i32 i32) (result i32)))
+            (table 3 funcref)
+            (elem (i32.const 0) $done $ping $pong)
+            (func $ping (type $step)
+                (i32.const 7)
+                (block (param i32) (result i32)
+                    (i32.const 8)
+                    (return_call_indirect (type $step)
+                        (i32.sub (local.get 0) (i32.const 1))
+                        (i32.add (local.get 1) (i32.const 1))
+                        (select (i32.const 2) (i32.const 0) (i32.gt_s (local.get 0) (i32.const 1))))))
+            (func $pong (type $step)
+                (return_call_indirect (type $step)
+                    (i32.sub (local.get 0) (i32.const 1))
+                    (i32.sub (local.get 1) (i32.const 2))
+                    (select (i32.const 1) (i32.const 0) (i32.gt_s (local.get 0) (i32.const 1)))))
+            (func (export "run") (param i32) (result i32)
+                (i32.add (i32.const 1) (call $ping (local.get 0) (i32.const 0)))))"#,
+        &mut imports,
+    );
+    let n = 1_000_000;
+    let result = call(&mut vm, &bytecode, "run", &[Value::i32(n)], &mut imports);
+    assert_eq!(result.as_i32(), Some(1 - n / 2));
+}
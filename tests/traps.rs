@@ -0,0 +1,57 @@
+mod common;
+
+use common::Instance;
+use weloce::{TrapKind, Value};
+
+const OPS: &str = r#"(module
+    (memory 1)
+    (func (export "i32.div_s") (param i32 i32) (result i32) (i32.div_s (local.get 0) (local.get 1)))
+    (func (export "i32.rem_u") (param i32 i32) (result i32) (i32.rem_u (local.get 0) (local.get 1)))
+    (func (export "i64.div_u") (param i64 i64) (result i64) (i64.div_u (local.get 0) (local.get 1)))
+    (func (export "i64.rem_s") (param i64 i64) (result i64) (i64.rem_s (local.get 0) (local.get 1)))
+    (func (export "unreachable") (unreachable))
+    (func (export "load") (param i32) (result i32) (i32.load (local.get 0)))
+    (func (export "trunc_s") (param f32) (result i32) (i32.trunc_f32_s (local.get 0)))
+    (func (export "trunc_u") (param f64) (result i64) (i64.trunc_f64_u (local.get 0))))"#;
+
+#[test]
+fn integer_division_traps() {
+    let mut ops = Instance::new(OPS);
+    assert_eq!(ops.trap("i32.div_s", &[Value::i32(1), Value::i32(0)]), TrapKind::IntegerDivideByZero);
+    assert_eq!(ops.trap("i32.div_s", &[Value::i32(i32::MIN), Value::i32(-1)]), TrapKind::IntegerOverflow);
+    assert_eq!(ops.trap("i32.rem_u", &[Value::i32(1), Value::i32(0)]), TrapKind::IntegerDivideByZero);
+    assert_eq!(ops.trap("i64.div_u", &[Value::i64(1), Value::i64(0)]), TrapKind::IntegerDivideByZero);
+    assert_eq!(ops.trap("i64.rem_s", &[Value::i64(1), Value::i64(0)]), TrapKind::IntegerDivideByZero);
+}
+
+#[test]
+fn signed_remainder_of_min_by_minus_one_is_zero() {
+    let mut ops = Instance::new(OPS);
+    assert_eq!(ops.call("i64.rem_s", &[Value::i64(i64::MIN), Value::i64(-1)]).as_i64(), Some(0));
+    assert_eq!(ops.call("i32.div_s", &[Value::i32(i32::MIN), Value::i32(1)]).as_i32(), Some(i32::MIN));
+}
+
+#[test]
+fn unreachable_traps() {
+    let mut ops = Instance::new(OPS);
+    assert_eq!(ops.trap("unreachable", &[]), TrapKind::Unreachable);
+}
+
+#[test]
+fn out_of_bounds_loads_trap() {
+    let mut ops = Instance::new(OPS);
+    assert_eq!(ops.call("load", &[Value::i32(65532)]).as_i32(), Some(0));
+    assert_eq!(ops.trap("load", &[Value::i32(65533)]), TrapKind::MemoryOutOfBounds);
+    assert_eq!(ops.trap("load", &[Value::i32(-1)]), TrapKind::MemoryOutOfBounds);
+}
+
+#[test]
+fn float_to_int_conversion_traps() {
+    let mut ops = Instance::new(OPS);
+    assert_eq!(ops.trap("trunc_s", &[Value::f32(f32::NAN)]), TrapKind::InvalidConversionToInteger);
+    assert_eq!(ops.trap("trunc_s", &[Value::f32(2147483648.0)]), TrapKind::IntegerOverflow);
+    assert_eq!(ops.trap("trunc_s", &[Value::f32(f32::NEG_INFINITY)]), TrapKind::IntegerOverflow);
+    assert_eq!(ops.call("trunc_s", &[Value::f32(-2147483648.0)]).as_i32(), Some(i32::MIN));
+    assert_eq!(ops.trap("trunc_u", &[Value::f64(-1.0)]), TrapKind::IntegerOverflow);
+    assert_eq!(ops.call("trunc_u", &[Value::f64(-0.9)]).as_i64(), Some(0));
+}
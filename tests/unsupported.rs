@@ -0,0 +1,50 @@
+use weloce::{compile_wasm, Imports};
+
+fn compile(wat: &str) -> anyhow::Result<()> {
+    let wasm = wat::parse_str(wat).unwrap();
+    compile_wasm(&wasm, &Imports::new()).map(|_| ())
+}
+
+#[test]
+fn typed_function_reference_param_is_an_error() {
+    assert!(compile(r#"(module (func (param (ref func))))"#).is_err());
+}
+
+#[test]
+fn anyref_local_is_an_error() {
+    assert!(compile(r#"(module (func (local anyref)))"#).is_err());
+}
+
+#[test]
+fn typed_reference_global_is_an_error() {
+    assert!(compile(r#"(module (func $f) (elem declare func $f) (global (ref func) (ref.func $f)))"#).is_err());
+}
+
+#[test]
+fn typed_reference_block_result_is_an_error() {
+    assert!(compile(r#"(module (func $f) (elem declare func $f) (func (drop (block (result (ref func)) (ref.func $f)))))"#).is_err());
+}
+
+#[test]
+fn gc_types_are_rejected() {
+    assert!(compile(r#"(module (type (struct (field i32))) (func (param (ref null 0))))"#).is_err());
+}
+
+#[test]
+fn gc_instructions_are_rejected() {
+    assert!(compile(r#"(module (func (result i32) (i31.get_s (ref.i31 (i32.const 1)))))"#).is_err());
+}
+
+#[test]
+fn shared_memories_are_rejected() {
+    assert!(compile(r#"(module (memory 1 1 shared))"#).is_err());
+}
+
+#[test]
+fn supported_reference_types_still_compile() {
+    compile(r#"(module
+        (tag $e (param externref))
+        (table 1 funcref)
+        (global (mut externref) (ref.null extern))
+        (func (param funcref exnref) (result externref) (ref.null extern)))"#).unwrap();
+}